@@ -1,7 +1,6 @@
 use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::fs;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -10,7 +9,9 @@ const ANILIST_API_URL: &str = "https://graphql.anilist.co";
 
 const SEARCH_CACHE_TTL_SECS: i64 = 60 * 60 * 12; // 12 hours
 const DETAIL_CACHE_TTL_SECS: i64 = 60 * 60 * 24; // 24 hours
-const PROGRESSIVE_CACHE_TTL_SECS: i64 = 60 * 60 * 24 * 7; // 7 days
+const RELATIONS_CACHE_TTL_SECS: i64 = 60 * 60 * 24 * 7; // 7 days
+const LIST_CACHE_TTL_SECS: i64 = 60 * 5; // 5 minutes
+const LIST_FULL_REFRESH_SECS: i64 = 60 * 60 * 24; // 24 hours
 
 fn current_ts_secs() -> i64 {
     SystemTime::now()
@@ -20,22 +21,7 @@ fn current_ts_secs() -> i64 {
 }
 
 fn cache_db_path() -> PathBuf {
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
-            let dir = PathBuf::from(local_app_data).join("PLAY-ON");
-            let _ = fs::create_dir_all(&dir);
-            return dir.join("anilist_cache.sqlite3");
-        }
-    }
-
-    if let Ok(home_dir) = std::env::var("HOME") {
-        let dir = PathBuf::from(home_dir).join(".play-on");
-        let _ = fs::create_dir_all(&dir);
-        return dir.join("anilist_cache.sqlite3");
-    }
-
-    std::env::temp_dir().join("playon_anilist_cache.sqlite3")
+    crate::app_data_dir().join("anilist_cache.sqlite3")
 }
 
 fn open_cache_db() -> Result<Connection, String> {
@@ -119,173 +105,37 @@ fn cache_set<T: serde::Serialize>(key: &str, value: &T, ttl_secs: i64) -> Result
     write_cache_json(key, &serialized, ttl_secs)
 }
 
-/// Result of a simple title search
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TitleSearchResult {
-    pub english: Option<String>,
-    pub romaji: Option<String>,
-}
-
-/// Result of progressive search with match info
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProgressiveSearchResult {
-    pub title: TitleSearchResult,
-    pub matched_query: String, // The query that matched
-    pub words_used: usize,     // How many words were used
-    pub total_words: usize,    // Total words in original title
-}
-
-#[derive(Debug, Deserialize)]
-struct SimpleTitleResponse {
-    #[serde(rename = "Media")]
-    media: Option<SimpleTitleMedia>,
-}
-
-#[derive(Debug, Deserialize)]
-struct SimpleTitleMedia {
-    title: TitleSearchResult,
-}
-
-/// Search anime title word-by-word, starting with 1 word
-///
-/// # Arguments
-/// * `title` - The parsed anime title to search
-///
-/// # Returns
-/// * `Result<Option<ProgressiveSearchResult>, String>` - Match result or error
-///
-/// # Strategy
-/// 1. Split title into words
-/// 2. Search with first word
-/// 3. If found, return the result
-/// 4. If not, add next word and try again
-/// 5. Continue until match found or all words tried
-pub async fn progressive_search_anime(
-    title: &str,
-) -> Result<Option<ProgressiveSearchResult>, String> {
-    let normalized_title = title.trim().to_lowercase();
-    if normalized_title.is_empty() {
-        return Ok(None);
-    }
-
-    let cache_key = format!("progressive:{}", normalized_title);
-    if let Some(cached) = cache_get::<Option<ProgressiveSearchResult>>(&cache_key, false)? {
-        println!("[AniList] Progressive cache hit for: {}", title);
-        return Ok(cached);
-    }
-
-    let words: Vec<&str> = normalized_title.split_whitespace().collect();
-    if words.is_empty() {
-        return Ok(None);
-    }
-
-    let total_words = words.len();
-    let client = reqwest::Client::new();
-
-    // Try progressively more words
-    for word_count in 1..=total_words {
-        let search_query: String = words[..word_count].join(" ");
-
-        println!(
-            "[AniList] Searching with {} word(s): \"{}\"",
-            word_count, search_query
-        );
-
-        let graphql_query = r#"
-            query Title($search: String) {
-                Media(search: $search, type: ANIME) {
-                    title {
-                        english
-                        romaji
-                    }
-                }
-            }
-        "#;
-
-        let request_body = json!({
-            "query": graphql_query,
-            "variables": { "search": search_query }
-        });
-
-        let response = client
-            .post(ANILIST_API_URL)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send request: {}", e))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            println!("[AniList] Progressive search failed: {} {}", status, body);
-
-            // On API failures (including rate limits), return stale cache if present.
-            if let Some(stale) = cache_get::<Option<ProgressiveSearchResult>>(&cache_key, true)? {
-                println!("[AniList] Returning stale progressive cache for: {}", title);
-                return Ok(stale);
-            }
-
-            return Err(format!("AniList progressive search failed: {}", status));
-        }
-
-        let anilist_response: AniListResponse<SimpleTitleResponse> = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-        if let Some(ref media) = anilist_response.data.media {
-            let english_lower = media
-                .title
-                .english
-                .as_ref()
-                .map(|s| s.to_lowercase())
-                .unwrap_or_default();
-            let romaji_lower = media
-                .title
-                .romaji
-                .as_ref()
-                .map(|s| s.to_lowercase())
-                .unwrap_or_default();
-
-            let title_matches = search_query
-                .split_whitespace()
-                .all(|word| english_lower.contains(word) || romaji_lower.contains(word));
-
-            if title_matches {
-                let result = Some(ProgressiveSearchResult {
-                    title: media.title.clone(),
-                    matched_query: search_query,
-                    words_used: word_count,
-                    total_words,
-                });
-                let _ = cache_set(&cache_key, &result, PROGRESSIVE_CACHE_TTL_SECS);
-                println!("[AniList] ✓ Valid match cached for: {}", title);
-                return Ok(result);
-            }
-        }
-    }
-
-    let no_match: Option<ProgressiveSearchResult> = None;
-    let _ = cache_set(&cache_key, &no_match, PROGRESSIVE_CACHE_TTL_SECS);
-    println!(
-        "[AniList] No valid match found after trying all {} words",
-        total_words
-    );
-    Ok(None)
-}
-
 /// Represents an anime from AniList
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Anime {
     pub id: i32,
+    /// MyAnimeList id, used to mirror updates to MAL
+    #[serde(rename = "idMal")]
+    pub id_mal: Option<i32>,
     pub title: AnimeTitle,
     #[serde(rename = "coverImage")]
     pub cover_image: CoverImage,
     pub episodes: Option<i32>,
+    /// Media format (TV, MOVIE, OVA, ONA, SPECIAL, ...)
+    pub format: Option<String>,
     pub status: Option<String>,
     pub description: Option<String>,
+    /// Year of the season the anime started airing in
+    #[serde(rename = "seasonYear", default)]
+    pub season_year: Option<i32>,
+    #[serde(rename = "startDate", default)]
+    pub start_date: Option<FuzzyDate>,
+    /// Alternative titles (abbreviations, other languages)
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+}
+
+impl Anime {
+    /// Release year: the season year, or the start date's year when AniList has no season
+    pub fn year(&self) -> Option<i32> {
+        self.season_year
+            .or_else(|| self.start_date.as_ref().and_then(|date| date.year))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -301,6 +151,14 @@ pub struct CoverImage {
     pub medium: Option<String>,
 }
 
+/// AniList date where any part may be unknown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyDate {
+    pub year: Option<i32>,
+    pub month: Option<i32>,
+    pub day: Option<i32>,
+}
+
 /// Response wrapper for AniList GraphQL queries
 #[derive(Debug, Deserialize)]
 struct AniListResponse<T> {
@@ -333,22 +191,37 @@ struct PageData {
 /// # Returns
 /// * `Result<Vec<Anime>, String>` - List of matching anime or error message
 pub async fn search_anime(query: &str, limit: i32) -> Result<Vec<Anime>, String> {
+    search_anime_with_formats(query, limit, &[]).await
+}
+
+/// Search for anime by title, restricted to the given AniList media formats
+/// (TV, TV_SHORT, MOVIE, SPECIAL, OVA, ONA, MUSIC); an empty list means any format
+pub async fn search_anime_with_formats(
+    query: &str,
+    limit: i32,
+    formats: &[&str],
+) -> Result<Vec<Anime>, String> {
     let normalized_query = query.trim().to_lowercase();
     if normalized_query.is_empty() {
         return Ok(Vec::new());
     }
 
-    let cache_key = format!("search:{}:{}", normalized_query, limit);
+    let cache_key = if formats.is_empty() {
+        format!("search:{}:{}", normalized_query, limit)
+    } else {
+        format!("search:{}:{}:{}", normalized_query, limit, formats.join(","))
+    };
     if let Some(cached) = cache_get::<Vec<Anime>>(&cache_key, false)? {
         println!("[AniList] Search cache hit: '{}'", query);
         return Ok(cached);
     }
 
     let graphql_query = r#"
-        query ($search: String, $perPage: Int) {
+        query ($search: String, $perPage: Int, $formats: [MediaFormat]) {
             Page(perPage: $perPage) {
-                media(search: $search, type: ANIME) {
+                media(search: $search, type: ANIME, format_in: $formats) {
                     id
+                    idMal
                     title {
                         romaji
                         english
@@ -359,8 +232,16 @@ pub async fn search_anime(query: &str, limit: i32) -> Result<Vec<Anime>, String>
                         medium
                     }
                     episodes
+                    format
                     status
                     description
+                    seasonYear
+                    startDate {
+                        year
+                        month
+                        day
+                    }
+                    synonyms
                 }
             }
         }
@@ -370,7 +251,8 @@ pub async fn search_anime(query: &str, limit: i32) -> Result<Vec<Anime>, String>
         "query": graphql_query,
         "variables": {
             "search": query,
-            "perPage": limit
+            "perPage": limit,
+            "formats": if formats.is_empty() { None } else { Some(formats) }
         }
     });
 
@@ -415,16 +297,30 @@ pub async fn search_anime(query: &str, limit: i32) -> Result<Vec<Anime>, String>
 /// # Returns
 /// * `Result<Anime, String>` - Anime details or error message
 pub async fn get_anime_by_id(id: i32) -> Result<Anime, String> {
-    let cache_key = format!("detail:{}", id);
-    if let Some(cached) = cache_get::<Anime>(&cache_key, false)? {
-        println!("[AniList] Detail cache hit: {}", id);
+    fetch_anime(&format!("detail:{}", id), json!({ "id": id })).await
+}
+
+/// Get anime details by MyAnimeList ID
+pub async fn get_anime_by_mal_id(id_mal: i32) -> Result<Anime, String> {
+    fetch_anime(
+        &format!("detail:mal:{}", id_mal),
+        json!({ "idMal": id_mal }),
+    )
+    .await
+}
+
+/// Fetch one anime by `id` or `idMal`, cached under `cache_key`
+async fn fetch_anime(cache_key: &str, variables: serde_json::Value) -> Result<Anime, String> {
+    if let Some(cached) = cache_get::<Anime>(cache_key, false)? {
+        println!("[AniList] Detail cache hit: {}", cache_key);
         return Ok(cached);
     }
 
     let graphql_query = r#"
-        query ($id: Int) {
-            Media(id: $id, type: ANIME) {
+        query ($id: Int, $idMal: Int) {
+            Media(id: $id, idMal: $idMal, type: ANIME) {
                 id
+                idMal
                 title {
                     romaji
                     english
@@ -435,15 +331,23 @@ pub async fn get_anime_by_id(id: i32) -> Result<Anime, String> {
                     medium
                 }
                 episodes
+                format
                 status
                 description
+                seasonYear
+                startDate {
+                    year
+                    month
+                    day
+                }
+                synonyms
             }
         }
     "#;
 
     let request_body = json!({
         "query": graphql_query,
-        "variables": { "id": id }
+        "variables": variables
     });
 
     let client = reqwest::Client::new();
@@ -461,8 +365,8 @@ pub async fn get_anime_by_id(id: i32) -> Result<Anime, String> {
         let body = response.text().await.unwrap_or_default();
         println!("[AniList] Detail fetch failed: {} {}", status, body);
 
-        if let Some(stale) = cache_get::<Anime>(&cache_key, true)? {
-            println!("[AniList] Returning stale detail cache: {}", id);
+        if let Some(stale) = cache_get::<Anime>(cache_key, true)? {
+            println!("[AniList] Returning stale detail cache: {}", cache_key);
             return Ok(stale);
         }
 
@@ -475,10 +379,159 @@ pub async fn get_anime_by_id(id: i32) -> Result<Anime, String> {
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
     let media = anilist_response.data.media;
-    let _ = cache_set(&cache_key, &media, DETAIL_CACHE_TTL_SECS);
+    let _ = cache_set(cache_key, &media, DETAIL_CACHE_TTL_SECS);
     Ok(media)
 }
 
+/// An anime with its directly related entries, one node of the relation graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaRelations {
+    pub id: i32,
+    pub episodes: Option<i32>,
+    pub format: Option<String>,
+    /// Related anime (manga adaptations and novels are left out)
+    pub relations: Vec<MediaRelation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaRelation {
+    /// AniList MediaRelation: SEQUEL, PREQUEL, SIDE_STORY, SPIN_OFF, ...
+    pub relation_type: String,
+    pub id: i32,
+    pub episodes: Option<i32>,
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationsResponse {
+    #[serde(rename = "Media")]
+    media: RelationsMedia,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationsMedia {
+    id: i32,
+    episodes: Option<i32>,
+    format: Option<String>,
+    relations: RelationConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationConnection {
+    edges: Vec<RelationEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationEdge {
+    #[serde(rename = "relationType")]
+    relation_type: Option<String>,
+    node: RelationNode,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelationNode {
+    id: i32,
+    episodes: Option<i32>,
+    format: Option<String>,
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+}
+
+/// Get an anime's related entries (sequels, prequels, ...) by ID
+///
+/// Each node is cached on its own, so walking a franchise only hits the API
+/// for seasons not seen in the last week.
+///
+/// # Arguments
+/// * `id` - The AniList anime ID
+///
+/// # Returns
+/// * `Result<MediaRelations, String>` - The anime's episode count, format and relations
+pub async fn get_media_relations(id: i32) -> Result<MediaRelations, String> {
+    let cache_key = format!("relations:{}", id);
+    if let Some(cached) = cache_get::<MediaRelations>(&cache_key, false)? {
+        println!("[AniList] Relations cache hit: {}", id);
+        return Ok(cached);
+    }
+
+    let graphql_query = r#"
+        query ($id: Int) {
+            Media(id: $id, type: ANIME) {
+                id
+                episodes
+                format
+                relations {
+                    edges {
+                        relationType
+                        node {
+                            id
+                            episodes
+                            format
+                            type
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+    let request_body = json!({
+        "query": graphql_query,
+        "variables": { "id": id }
+    });
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(ANILIST_API_URL)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        println!("[AniList] Relations fetch failed: {} {}", status, body);
+
+        if let Some(stale) = cache_get::<MediaRelations>(&cache_key, true)? {
+            println!("[AniList] Returning stale relations cache: {}", id);
+            return Ok(stale);
+        }
+
+        return Err(format!("AniList relations fetch failed: {}", status));
+    }
+
+    let anilist_response: AniListResponse<RelationsResponse> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    let media = anilist_response.data.media;
+    let relations = MediaRelations {
+        id: media.id,
+        episodes: media.episodes,
+        format: media.format,
+        relations: media
+            .relations
+            .edges
+            .into_iter()
+            .filter(|edge| edge.node.media_type.as_deref() == Some("ANIME"))
+            .filter_map(|edge| {
+                Some(MediaRelation {
+                    relation_type: edge.relation_type?,
+                    id: edge.node.id,
+                    episodes: edge.node.episodes,
+                    format: edge.node.format,
+                })
+            })
+            .collect(),
+    };
+    let _ = cache_set(&cache_key, &relations, RELATIONS_CACHE_TTL_SECS);
+    Ok(relations)
+}
+
 /// Search for anime by window title (fuzzy matching)
 /// This is useful for matching detected media player titles to AniList entries
 ///
@@ -488,18 +541,12 @@ pub async fn get_anime_by_id(id: i32) -> Result<Anime, String> {
 /// # Returns
 /// * `Result<Option<Anime>, String>` - Best matching anime or None if no good match
 pub async fn match_anime_from_title(window_title: &str) -> Result<Option<Anime>, String> {
-    // Clean up the window title (remove common suffixes like "- VLC media player")
-    let cleaned_title = window_title
-        .split(" - ")
-        .next()
-        .unwrap_or(window_title)
-        .trim();
+    // Parse the window title (player suffix, release tags, episode, year)
+    let parsed = crate::title_parser::parse_window_title(window_title);
 
-    // Search for the anime
-    let results = search_anime(cleaned_title, 5).await?;
-
-    // Return the first result (best match)
-    Ok(results.into_iter().next())
+    // Score the search candidates, None below the match threshold
+    let best = crate::title_matcher::match_parsed_title(&parsed).await?;
+    Ok(best.map(|m| m.anime))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -548,6 +595,92 @@ pub async fn exchange_code_for_token(
     Ok(token_data)
 }
 
+/// Failure of an authenticated AniList request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AniListError {
+    /// The access token was refused (expired or revoked); the user has to log in again
+    InvalidToken(String),
+    /// Anything else: network, rate limits, bad requests
+    Request(String),
+}
+
+impl std::fmt::Display for AniListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AniListError::InvalidToken(message) => write!(f, "AniList login expired: {}", message),
+            AniListError::Request(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Cache and other local failures
+impl From<String> for AniListError {
+    fn from(message: String) -> AniListError {
+        AniListError::Request(message)
+    }
+}
+
+impl From<AniListError> for String {
+    fn from(error: AniListError) -> String {
+        error.to_string()
+    }
+}
+
+/// Classify a failed authenticated request, marking a refused token invalid
+fn auth_request_error(access_token: &str, status: u16, body: &str, context: &str) -> AniListError {
+    if crate::anilist_auth::is_token_rejection(status, body) {
+        crate::anilist_auth::mark_invalid(access_token);
+        AniListError::InvalidToken(format!("{} ({})", context, status))
+    } else {
+        AniListError::Request(format!("{}: {}", context, body))
+    }
+}
+
+/// POST a GraphQL query, authenticated when `access_token` is given
+///
+/// A refused token comes back as `AniListError::InvalidToken`; `context`
+/// prefixes the message of any other failure.
+async fn post_graphql<T: serde::de::DeserializeOwned>(
+    query: &str,
+    variables: serde_json::Value,
+    access_token: Option<&str>,
+    context: &str,
+) -> Result<T, AniListError> {
+    let request_body = json!({
+        "query": query,
+        "variables": variables
+    });
+
+    let client = reqwest::Client::new();
+    let mut request = client
+        .post(ANILIST_API_URL)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json");
+    if let Some(token) = access_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+    let response = request
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| AniListError::Request(format!("Failed to send request: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(match access_token {
+            Some(token) => auth_request_error(token, status, &error_text, context),
+            None => AniListError::Request(format!("{}: {}", context, error_text)),
+        });
+    }
+
+    let anilist_response: AniListResponse<T> = response
+        .json()
+        .await
+        .map_err(|e| AniListError::Request(format!("Failed to parse response: {}", e)))?;
+    Ok(anilist_response.data)
+}
+
 /// Response from SaveMediaListEntry mutation
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MediaListEntry {
@@ -556,12 +689,6 @@ pub struct MediaListEntry {
     pub status: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct SaveMediaListResponse {
-    #[serde(rename = "SaveMediaListEntry")]
-    save_media_list_entry: MediaListEntry,
-}
-
 /// Update anime progress on AniList (requires authentication)
 ///
 /// # Arguments
@@ -571,22 +698,24 @@ struct SaveMediaListResponse {
 /// * `status` - Optional status (CURRENT, COMPLETED, PAUSED, DROPPED, PLANNING, REPEATING)
 ///
 /// # Returns
-/// * `Result<MediaListEntry, String>` - Updated entry or error message
+/// * `Result<MediaListEntry, AniListError>` - Updated entry, or why it failed
+///   (`InvalidToken` when the user has to log in again)
 pub async fn update_media_progress(
     access_token: &str,
     media_id: i32,
     progress: i32,
     status: Option<&str>,
-) -> Result<MediaListEntry, String> {
-    let graphql_mutation = r#"
-        mutation UpdateMediaProgress($mediaId: Int, $progress: Int, $status: MediaListStatus) {
-            SaveMediaListEntry(mediaId: $mediaId, progress: $progress, status: $status) {
-                id
-                progress
-                status
-            }
-        }
-    "#;
+) -> Result<MediaListEntry, AniListError> {
+    let graphql_mutation = format!(
+        r#"
+        mutation UpdateMediaProgress($mediaId: Int, $progress: Int, $status: MediaListStatus) {{
+            SaveMediaListEntry(mediaId: $mediaId, progress: $progress, status: $status) {{
+                ...listEntry
+            }}
+        }}
+        {}"#,
+        LIST_ENTRY_FRAGMENT
+    );
 
     let variables = if let Some(s) = status {
         json!({
@@ -601,31 +730,994 @@ pub async fn update_media_progress(
         })
     };
 
-    let request_body = json!({
-        "query": graphql_mutation,
-        "variables": variables
-    });
+    let data: SaveFullEntryResponse = post_graphql(
+        &graphql_mutation,
+        variables,
+        Some(access_token),
+        "Update failed",
+    )
+    .await?;
+    cache_saved_entry(&data.entry);
+
+    Ok(MediaListEntry {
+        id: data.entry.id,
+        progress: data.entry.progress,
+        status: data.entry.status,
+    })
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(ANILIST_API_URL)
-        .header("Content-Type", "application/json")
-        .header("Accept", "application/json")
-        .header("Authorization", format!("Bearer {}", access_token))
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+#[derive(Debug, Deserialize)]
+struct ListEntryMedia {
+    #[serde(rename = "mediaListEntry")]
+    media_list_entry: Option<MediaListEntry>,
+}
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Update failed: {}", error_text));
-    }
+#[derive(Debug, Deserialize)]
+struct ListEntryResponse {
+    #[serde(rename = "Media")]
+    media: ListEntryMedia,
+}
 
-    let anilist_response: AniListResponse<SaveMediaListResponse> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+/// Get the authenticated user's list entry for a media (requires authentication)
+///
+/// # Returns
+/// * `Ok(None)` when the media isn't on the user's list
+pub async fn get_media_list_entry(
+    access_token: &str,
+    media_id: i32,
+) -> Result<Option<MediaListEntry>, AniListError> {
+    let graphql_query = r#"
+        query ($id: Int) {
+            Media(id: $id) {
+                mediaListEntry {
+                    id
+                    progress
+                    status
+                }
+            }
+        }
+    "#;
+
+    let data: ListEntryResponse = post_graphql(
+        graphql_query,
+        json!({ "id": media_id }),
+        Some(access_token),
+        "List entry lookup failed",
+    )
+    .await?;
+
+    Ok(data.media.media_list_entry)
+}
+
+/// One entry of a user's anime or manga list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserListEntry {
+    /// List entry ID (not the media ID)
+    pub id: i32,
+    pub media_id: i32,
+    /// MediaListStatus (CURRENT, PLANNING, COMPLETED, DROPPED, PAUSED, REPEATING)
+    pub status: String,
+    /// Episodes watched or chapters read
+    pub progress: i32,
+    /// Volumes read (manga only)
+    #[serde(default)]
+    pub progress_volumes: Option<i32>,
+    /// Score on the 100-point scale, whichever format the user displays
+    pub score: f64,
+    /// Rewatch/reread count
+    pub repeat: i32,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Hidden from other users
+    #[serde(default)]
+    pub private: bool,
+    /// Only shown in custom lists
+    #[serde(default)]
+    pub hidden_from_status_lists: bool,
+    /// The user's custom lists and whether the entry is in each
+    #[serde(default)]
+    pub custom_lists: Option<Vec<CustomListFlag>>,
+    pub started_at: FuzzyDate,
+    pub completed_at: FuzzyDate,
+    /// Unix time (seconds) of the entry's last change
+    pub updated_at: i64,
+    pub media: ListMedia,
+}
 
-    Ok(anilist_response.data.save_media_list_entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomListFlag {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// The media an entry is for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListMedia {
+    pub id: i32,
+    pub id_mal: Option<i32>,
+    pub title: AnimeTitle,
+    pub cover_image: CoverImage,
+    pub format: Option<String>,
+    pub status: Option<String>,
+    pub episodes: Option<i32>,
+    pub chapters: Option<i32>,
+    pub volumes: Option<i32>,
+}
+
+/// A user's whole list of one media type, as cached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedMediaList {
+    user_id: i32,
+    /// Most recently updated first
+    entries: Vec<UserListEntry>,
+    /// Unix time of the last full fetch (incremental refreshes don't see deletions)
+    full_fetched_at: i64,
+}
+
+/// Fields fetched for every list entry
+const LIST_ENTRY_FRAGMENT: &str = r#"
+    fragment listEntry on MediaList {
+        id
+        mediaId
+        status
+        progress
+        progressVolumes
+        score(format: POINT_100)
+        repeat
+        notes
+        private
+        hiddenFromStatusLists
+        customLists(asArray: true)
+        startedAt { year month day }
+        completedAt { year month day }
+        updatedAt
+        media {
+            id
+            idMal
+            title { romaji english native }
+            coverImage { large medium }
+            format
+            status
+            episodes
+            chapters
+            volumes
+        }
+    }
+"#;
+
+const LIST_CHUNK_SIZE: i32 = 500;
+const LIST_PAGE_SIZE: i32 = 50;
+
+#[derive(Debug, Deserialize)]
+struct MediaListCollectionResponse {
+    #[serde(rename = "MediaListCollection")]
+    collection: MediaListCollection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaListCollection {
+    user: ListUser,
+    #[serde(default)]
+    has_next_chunk: bool,
+    lists: Vec<MediaListGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListUser {
+    id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListGroup {
+    entries: Vec<UserListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaListPageResponse {
+    #[serde(rename = "Page")]
+    page: MediaListPage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaListPage {
+    page_info: PageInfo,
+    media_list: Vec<UserListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+}
+
+/// Get a user's anime or manga list
+///
+/// The whole list is cached; within `LIST_CACHE_TTL_SECS` it is served from
+/// the cache, after that only entries changed since the newest cached
+/// `updatedAt` are fetched and merged in. A full fetch every
+/// `LIST_FULL_REFRESH_SECS` drops entries removed on AniList. Entries saved
+/// through this module are written into the cached list right away.
+///
+/// # Arguments
+/// * `user` - AniList user name, or numeric user ID
+/// * `media_type` - ANIME or MANGA
+/// * `status` - Only entries with this MediaListStatus (None = all)
+/// * `access_token` - Needed for private lists; refused tokens come back as `InvalidToken`
+///
+/// # Returns
+/// * Entries, most recently updated first
+pub async fn get_user_media_list(
+    user: &str,
+    media_type: &str,
+    status: Option<&str>,
+    access_token: Option<&str>,
+) -> Result<Vec<UserListEntry>, AniListError> {
+    let user = user.trim();
+    let media_type = media_type.to_uppercase();
+    if user.is_empty() {
+        return Err(AniListError::Request("No user given".to_string()));
+    }
+    if media_type != "ANIME" && media_type != "MANGA" {
+        return Err(AniListError::Request(format!(
+            "Unknown media type: {}",
+            media_type
+        )));
+    }
+
+    let cache_key = format!("list:{}:{}", user, media_type);
+    let list = match cache_get::<CachedMediaList>(&cache_key, false)? {
+        Some(cached) => {
+            println!("[AniList] List cache hit: {}", cache_key);
+            cached
+        }
+        None => {
+            let stale = cache_get::<CachedMediaList>(&cache_key, true)?;
+            match refresh_media_list(user, &media_type, stale.clone(), access_token).await {
+                Ok(list) => {
+                    let _ = cache_set(&cache_key, &list, LIST_CACHE_TTL_SECS);
+                    list
+                }
+                // A refused token needs the user's attention, not stale data
+                Err(AniListError::Request(e)) => match stale {
+                    Some(stale) => {
+                        println!(
+                            "[AniList] List refresh failed ({}), returning stale cache",
+                            e
+                        );
+                        stale
+                    }
+                    None => return Err(AniListError::Request(e)),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    };
+
+    let entries = match status {
+        Some(status) => list
+            .entries
+            .into_iter()
+            .filter(|entry| entry.status.eq_ignore_ascii_case(status))
+            .collect(),
+        None => list.entries,
+    };
+    Ok(entries)
+}
+
+#[derive(Debug, Deserialize)]
+struct ViewerResponse {
+    #[serde(rename = "Viewer")]
+    viewer: ListUser,
+}
+
+/// AniList ID of the user `access_token` belongs to
+async fn get_viewer_id(access_token: &str) -> Result<i32, AniListError> {
+    let data: ViewerResponse = post_graphql(
+        "query { Viewer { id } }",
+        json!({}),
+        Some(access_token),
+        "Viewer lookup failed",
+    )
+    .await?;
+    Ok(data.viewer.id)
+}
+
+/// Progress on the stored login's anime list for a media
+///
+/// Read from the cached list (see `get_user_media_list`), so detection can
+/// ask on every new episode without a request each time.
+///
+/// # Returns
+/// * `Ok(None)` when nobody is logged in or the media isn't on the list
+pub async fn viewer_list_progress(media_id: i32) -> Result<Option<i32>, AniListError> {
+    let token = match crate::anilist_auth::access_token() {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    let user_id = match crate::anilist_auth::user_id() {
+        Some(user_id) => user_id,
+        None => {
+            let user_id = get_viewer_id(&token).await?;
+            crate::anilist_auth::set_user_id(&token, user_id);
+            user_id
+        }
+    };
+
+    let entries = get_user_media_list(&user_id.to_string(), "ANIME", None, Some(&token)).await?;
+    Ok(list_progress(&entries, media_id))
+}
+
+fn list_progress(entries: &[UserListEntry], media_id: i32) -> Option<i32> {
+    entries
+        .iter()
+        .find(|entry| entry.media_id == media_id)
+        .map(|entry| entry.progress)
+}
+
+/// Bring a cached list up to date: incrementally when there is a recent
+/// enough full fetch to build on, completely otherwise
+async fn refresh_media_list(
+    user: &str,
+    media_type: &str,
+    cached: Option<CachedMediaList>,
+    access_token: Option<&str>,
+) -> Result<CachedMediaList, AniListError> {
+    let now = current_ts_secs();
+    let mut list = match cached {
+        Some(list) if now - list.full_fetched_at < LIST_FULL_REFRESH_SECS => list,
+        _ => return fetch_full_media_list(user, media_type, access_token).await,
+    };
+
+    let since = list.entries.iter().map(|e| e.updated_at).max().unwrap_or(0);
+    let updates = fetch_list_updates(list.user_id, media_type, since, access_token).await?;
+    println!(
+        "[AniList] {} list entr(ies) of {} changed since {}",
+        updates.len(),
+        user,
+        since
+    );
+    merge_list_updates(&mut list.entries, updates);
+    Ok(list)
+}
+
+/// Fetch a whole list through MediaListCollection, chunk by chunk
+async fn fetch_full_media_list(
+    user: &str,
+    media_type: &str,
+    access_token: Option<&str>,
+) -> Result<CachedMediaList, AniListError> {
+    let query = format!(
+        r#"
+        query ($userId: Int, $userName: String, $type: MediaType, $chunk: Int, $perChunk: Int) {{
+            MediaListCollection(
+                userId: $userId,
+                userName: $userName,
+                type: $type,
+                chunk: $chunk,
+                perChunk: $perChunk,
+                forceSingleCompletedList: true
+            ) {{
+                user {{ id }}
+                hasNextChunk
+                lists {{
+                    entries {{ ...listEntry }}
+                }}
+            }}
+        }}
+        {}"#,
+        LIST_ENTRY_FRAGMENT
+    );
+
+    let user_id = user.parse::<i32>().ok();
+    let mut entries = Vec::new();
+    let mut chunk = 1;
+    loop {
+        let variables = json!({
+            "userId": user_id,
+            "userName": if user_id.is_some() { None } else { Some(user) },
+            "type": media_type,
+            "chunk": chunk,
+            "perChunk": LIST_CHUNK_SIZE
+        });
+        let data: MediaListCollectionResponse =
+            post_graphql(&query, variables, access_token, "List fetch failed").await?;
+        let collection = data.collection;
+
+        // Custom lists repeat entries that are also in a status list
+        let chunk_entries = collection.lists.into_iter().flat_map(|list| list.entries);
+        merge_list_updates(&mut entries, chunk_entries.collect());
+
+        if !collection.has_next_chunk {
+            println!(
+                "[AniList] Fetched {} {} list entr(ies) of {}",
+                entries.len(),
+                media_type,
+                user
+            );
+            return Ok(CachedMediaList {
+                user_id: collection.user.id,
+                entries,
+                full_fetched_at: current_ts_secs(),
+            });
+        }
+        chunk += 1;
+    }
+}
+
+/// Fetch entries changed at or after `since`, newest first, page by page
+async fn fetch_list_updates(
+    user_id: i32,
+    media_type: &str,
+    since: i64,
+    access_token: Option<&str>,
+) -> Result<Vec<UserListEntry>, AniListError> {
+    let query = format!(
+        r#"
+        query ($userId: Int, $type: MediaType, $page: Int, $perPage: Int) {{
+            Page(page: $page, perPage: $perPage) {{
+                pageInfo {{ hasNextPage }}
+                mediaList(userId: $userId, type: $type, sort: UPDATED_TIME_DESC) {{
+                    ...listEntry
+                }}
+            }}
+        }}
+        {}"#,
+        LIST_ENTRY_FRAGMENT
+    );
+
+    let mut updates = Vec::new();
+    let mut page = 1;
+    loop {
+        let variables = json!({
+            "userId": user_id,
+            "type": media_type,
+            "page": page,
+            "perPage": LIST_PAGE_SIZE
+        });
+        let data: MediaListPageResponse =
+            post_graphql(&query, variables, access_token, "List refresh failed").await?;
+
+        // Same-second changes are fetched again; merging replaces them
+        let mut reached_known = false;
+        for entry in data.page.media_list {
+            if entry.updated_at < since {
+                reached_known = true;
+                break;
+            }
+            updates.push(entry);
+        }
+        if reached_known || !data.page.page_info.has_next_page {
+            return Ok(updates);
+        }
+        page += 1;
+    }
+}
+
+/// Replace entries by ID with their updated versions, newest first
+fn merge_list_updates(entries: &mut Vec<UserListEntry>, updates: Vec<UserListEntry>) {
+    for update in updates {
+        match entries.iter_mut().find(|entry| entry.id == update.id) {
+            Some(entry) => *entry = update,
+            None => entries.push(update),
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.updated_at));
+}
+
+/// Changes to a list entry; fields left as None are not touched
+///
+/// Serializes to the SaveMediaListEntry variables as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaListUpdate {
+    pub media_id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Score in the user's own scoring format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Score on the 100-point scale, whatever format the user displays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_raw: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_volumes: Option<i32>,
+    /// Rewatch/reread count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<i32>,
+    /// Empty string clears the notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_from_status_lists: Option<bool>,
+    /// Names of the custom lists the entry should be in (replaces the current set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_lists: Option<Vec<String>>,
+    /// A date with every part null clears it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<FuzzyDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<FuzzyDate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveFullEntryResponse {
+    #[serde(rename = "SaveMediaListEntry")]
+    entry: UserListEntry,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteEntryResponse {
+    #[serde(rename = "DeleteMediaListEntry")]
+    result: Deleted,
+}
+
+#[derive(Debug, Deserialize)]
+struct Deleted {
+    deleted: Option<bool>,
+}
+
+/// Create or update a list entry (requires authentication)
+///
+/// # Arguments
+/// * `access_token` - OAuth access token for authentication
+/// * `update` - Media ID and the fields to change
+///
+/// # Returns
+/// * `Result<UserListEntry, AniListError>` - The entry as saved
+pub async fn save_media_list_entry(
+    access_token: &str,
+    update: &MediaListUpdate,
+) -> Result<UserListEntry, AniListError> {
+    let graphql_mutation = format!(
+        r#"
+        mutation (
+            $mediaId: Int,
+            $status: MediaListStatus,
+            $score: Float,
+            $scoreRaw: Int,
+            $progress: Int,
+            $progressVolumes: Int,
+            $repeat: Int,
+            $notes: String,
+            $private: Boolean,
+            $hiddenFromStatusLists: Boolean,
+            $customLists: [String],
+            $startedAt: FuzzyDateInput,
+            $completedAt: FuzzyDateInput
+        ) {{
+            SaveMediaListEntry(
+                mediaId: $mediaId,
+                status: $status,
+                score: $score,
+                scoreRaw: $scoreRaw,
+                progress: $progress,
+                progressVolumes: $progressVolumes,
+                repeat: $repeat,
+                notes: $notes,
+                private: $private,
+                hiddenFromStatusLists: $hiddenFromStatusLists,
+                customLists: $customLists,
+                startedAt: $startedAt,
+                completedAt: $completedAt
+            ) {{
+                ...listEntry
+            }}
+        }}
+        {}"#,
+        LIST_ENTRY_FRAGMENT
+    );
+
+    // Variables left out (None) leave the entry's fields as they are
+    let variables = serde_json::to_value(update)
+        .map_err(|e| AniListError::Request(format!("Failed to encode update: {}", e)))?;
+    let data: SaveFullEntryResponse = post_graphql(
+        &graphql_mutation,
+        variables,
+        Some(access_token),
+        "Save failed",
+    )
+    .await?;
+
+    println!(
+        "[AniList] Saved list entry {} for media {}",
+        data.entry.id, data.entry.media_id
+    );
+    cache_saved_entry(&data.entry);
+    Ok(data.entry)
+}
+
+/// Remove an entry from the user's list (requires authentication)
+///
+/// # Arguments
+/// * `access_token` - OAuth access token for authentication
+/// * `entry_id` - List entry ID (`UserListEntry::id`, not the media ID)
+///
+/// # Returns
+/// * `Result<bool, AniListError>` - Whether AniList deleted the entry
+pub async fn delete_media_list_entry(
+    access_token: &str,
+    entry_id: i32,
+) -> Result<bool, AniListError> {
+    let graphql_mutation = r#"
+        mutation ($id: Int) {
+            DeleteMediaListEntry(id: $id) {
+                deleted
+            }
+        }
+    "#;
+
+    let data: DeleteEntryResponse = post_graphql(
+        graphql_mutation,
+        json!({ "id": entry_id }),
+        Some(access_token),
+        "Delete failed",
+    )
+    .await?;
+    let deleted = data.result.deleted.unwrap_or(false);
+
+    // Incremental list refreshes can't see deletions; start the lists over
+    if deleted {
+        let _ = drop_cached_lists();
+    }
+    Ok(deleted)
+}
+
+/// Remove every cached user list, so the next read fetches it in full
+fn drop_cached_lists() -> Result<(), String> {
+    let conn = open_cache_db()?;
+    conn.execute("DELETE FROM anilist_cache WHERE key LIKE 'list:%'", [])
+        .map_err(|e| format!("Failed to clear cached lists: {}", e))?;
+    Ok(())
+}
+
+/// Put a saved entry into the cached lists, so reads within
+/// `LIST_CACHE_TTL_SECS` don't return it as it was before the save
+fn cache_saved_entry(entry: &UserListEntry) {
+    if let Err(e) = update_cached_lists(entry) {
+        println!("[AniList] Failed to update cached lists: {}", e);
+        let _ = drop_cached_lists();
+    }
+}
+
+fn update_cached_lists(entry: &UserListEntry) -> Result<(), String> {
+    let conn = open_cache_db()?;
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM anilist_cache WHERE key LIKE 'list:%'")
+        .map_err(|e| format!("Failed to read cached lists: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| format!("Failed to read cached lists: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read cached lists: {}", e))?;
+
+    let mut found = false;
+    for (key, value) in rows {
+        let mut list: CachedMediaList = serde_json::from_str(&value)
+            .map_err(|e| format!("Failed to decode cached list: {}", e))?;
+        if !fold_saved_entry(&mut list, entry) {
+            continue;
+        }
+        found = true;
+        let value = serde_json::to_string(&list)
+            .map_err(|e| format!("Failed to encode cached list: {}", e))?;
+        conn.execute(
+            "UPDATE anilist_cache SET value = ?1 WHERE key = ?2",
+            params![value, key],
+        )
+        .map_err(|e| format!("Failed to write cached list: {}", e))?;
+    }
+
+    // A new entry: whose list it joins isn't known here, so let the next
+    // read pick it up with an incremental refresh
+    if !found {
+        conn.execute(
+            "UPDATE anilist_cache SET expires_at = 0 WHERE key LIKE 'list:%'",
+            [],
+        )
+        .map_err(|e| format!("Failed to expire cached lists: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Replace `entry` in a cached list that holds it
+///
+/// # Returns
+/// * Whether the list held the entry
+fn fold_saved_entry(list: &mut CachedMediaList, entry: &UserListEntry) -> bool {
+    if !list.entries.iter().any(|e| e.id == entry.id) {
+        return false;
+    }
+    merge_list_updates(&mut list.entries, vec![entry.clone()]);
+    true
+}
+
+/// Represents a manga (or light novel / one-shot) from AniList
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manga {
+    pub id: i32,
+    /// MyAnimeList id, used to mirror updates to MAL
+    #[serde(rename = "idMal")]
+    pub id_mal: Option<i32>,
+    pub title: AnimeTitle,
+    #[serde(rename = "coverImage")]
+    pub cover_image: CoverImage,
+    /// Chapter count (None while releasing)
+    pub chapters: Option<i32>,
+    /// Volume count (None while releasing)
+    pub volumes: Option<i32>,
+    /// Media format (MANGA, NOVEL, ONE_SHOT)
+    pub format: Option<String>,
+    /// Publishing status (FINISHED, RELEASING, HIATUS, ...)
+    pub status: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "startDate", default)]
+    pub start_date: Option<FuzzyDate>,
+    /// Alternative titles (abbreviations, other languages)
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+}
+
+/// Fields fetched for every manga
+const MANGA_FRAGMENT: &str = r#"
+    fragment mangaFields on Media {
+        id
+        idMal
+        title { romaji english native }
+        coverImage { large medium }
+        chapters
+        volumes
+        format
+        status
+        description
+        startDate { year month day }
+        synonyms
+    }
+"#;
+
+#[derive(Debug, Deserialize)]
+struct MangaSearchResponse {
+    #[serde(rename = "Page")]
+    page: MangaPageData,
+}
+
+#[derive(Debug, Deserialize)]
+struct MangaPageData {
+    media: Vec<Manga>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MangaResponse {
+    #[serde(rename = "Media")]
+    media: Manga,
+}
+
+/// Search for manga by title
+///
+/// # Arguments
+/// * `query` - The search query (manga title)
+/// * `limit` - Maximum number of results to return
+///
+/// # Returns
+/// * `Result<Vec<Manga>, String>` - List of matching manga or error message
+pub async fn search_manga(query: &str, limit: i32) -> Result<Vec<Manga>, String> {
+    let normalized_query = query.trim().to_lowercase();
+    if normalized_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let cache_key = format!("manga-search:{}:{}", normalized_query, limit);
+    if let Some(cached) = cache_get::<Vec<Manga>>(&cache_key, false)? {
+        println!("[AniList] Manga search cache hit: '{}'", query);
+        return Ok(cached);
+    }
+
+    let graphql_query = format!(
+        r#"
+        query ($search: String, $perPage: Int) {{
+            Page(perPage: $perPage) {{
+                media(search: $search, type: MANGA) {{
+                    ...mangaFields
+                }}
+            }}
+        }}
+        {}"#,
+        MANGA_FRAGMENT
+    );
+    let variables = json!({ "search": query, "perPage": limit });
+
+    match post_graphql::<MangaSearchResponse>(
+        &graphql_query,
+        variables,
+        None,
+        "Manga search failed",
+    )
+    .await
+    {
+        Ok(data) => {
+            let results = data.page.media;
+            let _ = cache_set(&cache_key, &results, SEARCH_CACHE_TTL_SECS);
+            Ok(results)
+        }
+        Err(e) => {
+            println!("[AniList] {}", e);
+            if let Some(stale) = cache_get::<Vec<Manga>>(&cache_key, true)? {
+                println!("[AniList] Returning stale manga search cache: '{}'", query);
+                return Ok(stale);
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Get manga details (chapters, volumes, status) by ID
+///
+/// # Arguments
+/// * `id` - The AniList manga ID
+///
+/// # Returns
+/// * `Result<Manga, String>` - Manga details or error message
+pub async fn get_manga_by_id(id: i32) -> Result<Manga, String> {
+    let cache_key = format!("manga-detail:{}", id);
+    if let Some(cached) = cache_get::<Manga>(&cache_key, false)? {
+        println!("[AniList] Manga detail cache hit: {}", id);
+        return Ok(cached);
+    }
+
+    let graphql_query = format!(
+        r#"
+        query ($id: Int) {{
+            Media(id: $id, type: MANGA) {{
+                ...mangaFields
+            }}
+        }}
+        {}"#,
+        MANGA_FRAGMENT
+    );
+
+    match post_graphql::<MangaResponse>(
+        &graphql_query,
+        json!({ "id": id }),
+        None,
+        "Manga detail fetch failed",
+    )
+    .await
+    {
+        Ok(data) => {
+            let _ = cache_set(&cache_key, &data.media, DETAIL_CACHE_TTL_SECS);
+            Ok(data.media)
+        }
+        Err(e) => {
+            println!("[AniList] {}", e);
+            if let Some(stale) = cache_get::<Manga>(&cache_key, true)? {
+                println!("[AniList] Returning stale manga detail cache: {}", id);
+                return Ok(stale);
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Update manga reading progress on AniList (requires authentication)
+///
+/// # Arguments
+/// * `access_token` - OAuth access token for authentication
+/// * `media_id` - AniList manga ID
+/// * `chapters` - Chapters read (AniList counts whole chapters)
+/// * `volumes` - Volumes read, if known
+/// * `status` - Optional status (CURRENT, COMPLETED, PAUSED, DROPPED, PLANNING, REPEATING)
+///
+/// # Returns
+/// * `Result<UserListEntry, AniListError>` - The entry as saved
+pub async fn update_manga_progress(
+    access_token: &str,
+    media_id: i32,
+    chapters: i32,
+    volumes: Option<i32>,
+    status: Option<&str>,
+) -> Result<UserListEntry, AniListError> {
+    let update = MediaListUpdate {
+        media_id,
+        status: status.map(String::from),
+        progress: Some(chapters),
+        progress_volumes: volumes,
+        ..Default::default()
+    };
+    save_media_list_entry(access_token, &update).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i32, progress: i32, updated_at: i64) -> UserListEntry {
+        serde_json::from_value(json!({
+            "id": id,
+            "mediaId": id * 10,
+            "status": "CURRENT",
+            "progress": progress,
+            "score": 0.0,
+            "repeat": 0,
+            "startedAt": { "year": null, "month": null, "day": null },
+            "completedAt": { "year": null, "month": null, "day": null },
+            "updatedAt": updated_at,
+            "media": {
+                "id": id * 10,
+                "idMal": null,
+                "title": { "romaji": "Title", "english": null, "native": null },
+                "coverImage": { "large": null, "medium": null },
+                "format": "TV",
+                "status": "RELEASING",
+                "episodes": 12,
+                "chapters": null,
+                "volumes": null
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_list_updates() {
+        let mut entries = vec![entry(1, 3, 300), entry(2, 5, 200), entry(3, 1, 100)];
+        merge_list_updates(&mut entries, vec![entry(3, 2, 400), entry(4, 1, 350)]);
+
+        let order: Vec<(i32, i32)> = entries.iter().map(|e| (e.id, e.progress)).collect();
+        assert_eq!(order, vec![(3, 2), (4, 1), (1, 3), (2, 5)]);
+    }
+
+    #[test]
+    fn test_update_sends_only_set_fields() {
+        let update = MediaListUpdate {
+            media_id: 21,
+            score_raw: Some(85),
+            notes: Some(String::new()),
+            completed_at: Some(FuzzyDate {
+                year: Some(2024),
+                month: Some(3),
+                day: None,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            json!({
+                "mediaId": 21,
+                "scoreRaw": 85,
+                "notes": "",
+                "completedAt": { "year": 2024, "month": 3, "day": null }
+            })
+        );
+    }
+
+    #[test]
+    fn test_list_progress_looks_up_media() {
+        let entries = vec![entry(1, 10, 300), entry(2, 5, 200)];
+        assert_eq!(list_progress(&entries, 10), Some(10));
+        assert_eq!(list_progress(&entries, 20), Some(5));
+        assert_eq!(list_progress(&entries, 30), None);
+    }
+
+    #[test]
+    fn test_saved_entry_replaces_cached_one() {
+        let mut list = CachedMediaList {
+            user_id: 1,
+            entries: vec![entry(1, 3, 300), entry(2, 5, 200)],
+            full_fetched_at: 0,
+        };
+        assert!(fold_saved_entry(&mut list, &entry(2, 6, 400)));
+        let order: Vec<(i32, i32)> = list.entries.iter().map(|e| (e.id, e.progress)).collect();
+        assert_eq!(order, vec![(2, 6), (1, 3)]);
+
+        assert!(!fold_saved_entry(&mut list, &entry(3, 1, 500)));
+        assert_eq!(list.entries.len(), 2);
+    }
 }
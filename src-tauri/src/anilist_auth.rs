@@ -0,0 +1,408 @@
+//! AniList Auth Module
+//!
+//! PURPOSE: Keep the AniList access token in the backend together with its
+//! expiry, so background updates can use it and an expired or revoked token
+//! is noticed instead of failing silently
+//!
+//! APPROACH: The token from the OAuth code exchange is saved to
+//! `anilist_token.json` in the app data directory (`~/.play-on`, or
+//! `%LOCALAPPDATA%\PLAY-ON` on Windows) with its absolute expiry. The file is
+//! plaintext, so on unix it is created readable by the user only (0600); on
+//! Windows the profile folder's permissions apply. `check_expiry` (polled by
+//! `run_token_watch` in lib.rs) reports once when the token enters its last
+//! `EXPIRY_WARNING`, and again when it expires. AniList answers requests made
+//! with a bad token with 401 or an "Invalid token" GraphQL error;
+//! `is_token_rejection` spots both so the caller can mark the token invalid
+//! and ask for a new login. AniList has no refresh grant, so re-auth always
+//! means logging in again.
+
+use crate::anilist::TokenResponse;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long before expiry the user is warned
+const EXPIRY_WARNING: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The stored AniList login
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoredToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix time (seconds) the token expires at
+    pub expires_at: i64,
+    /// Set once AniList has rejected the token
+    #[serde(default)]
+    pub invalid: bool,
+    /// AniList ID of the user the token belongs to, once looked up
+    #[serde(default)]
+    pub user_id: Option<i32>,
+}
+
+/// Token state reported to the frontend
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TokenStatus {
+    pub logged_in: bool,
+    pub expires_at: Option<i64>,
+    pub expires_in_secs: Option<i64>,
+    /// Within `EXPIRY_WARNING` of expiring
+    pub expiring_soon: bool,
+    /// Expired or rejected: the user has to log in again
+    pub needs_reauth: bool,
+}
+
+/// Something the user should hear about the token
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TokenNotice {
+    Expiring {
+        expires_at: i64,
+        expires_in_secs: i64,
+    },
+    Expired {
+        expires_at: i64,
+    },
+}
+
+struct TokenState {
+    token: Option<StoredToken>,
+    /// Last notice reported for the current token, so each is sent once
+    notified: Option<TokenNotice>,
+}
+
+lazy_static::lazy_static! {
+    static ref TOKEN: Mutex<TokenState> = Mutex::new(TokenState {
+        token: load_token_file(),
+        notified: None,
+    });
+}
+
+fn current_ts_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs() as i64
+}
+
+fn token_path() -> PathBuf {
+    crate::app_data_dir().join("anilist_token.json")
+}
+
+fn load_token_file() -> Option<StoredToken> {
+    let json = std::fs::read_to_string(token_path()).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(token) => Some(token),
+        Err(e) => {
+            eprintln!("[AniList Auth] Ignoring unreadable token file: {}", e);
+            None
+        }
+    }
+}
+
+fn save_token_file(token: Option<&StoredToken>) -> Result<(), String> {
+    let path = token_path();
+    match token {
+        Some(token) => {
+            let json = serde_json::to_string_pretty(token)
+                .map_err(|e| format!("Failed to encode token: {}", e))?;
+            write_private(&path, json.as_bytes())
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        }
+        None if path.exists() => std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+        None => Ok(()),
+    }
+}
+
+/// Write a file only the current user can read
+///
+/// Shared by every settings file that holds a credential.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies on creation; tighten a file written before
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents)
+}
+
+fn replace_token(token: Option<StoredToken>) -> Result<(), String> {
+    save_token_file(token.as_ref())?;
+    let mut state = TOKEN.lock().map_err(|_| "Token lock error")?;
+    state.token = token;
+    state.notified = None;
+    Ok(())
+}
+
+/// Store the token from an OAuth code exchange
+pub fn store_token_response(response: &TokenResponse) -> Result<StoredToken, String> {
+    let token = StoredToken {
+        access_token: response.access_token.clone(),
+        refresh_token: response.refresh_token.clone(),
+        expires_at: current_ts_secs() + response.expires_in as i64,
+        invalid: false,
+        user_id: None,
+    };
+    replace_token(Some(token.clone()))?;
+    println!(
+        "[AniList Auth] Stored token, expires at {}",
+        token.expires_at
+    );
+    Ok(token)
+}
+
+/// Store a token the frontend already holds (e.g. from before the backend kept one)
+///
+/// AniList tokens are JWTs, so the expiry is read from their `exp` claim.
+pub fn store_access_token(access_token: &str) -> Result<StoredToken, String> {
+    let expires_at = jwt_expiry(access_token)
+        .ok_or_else(|| "Token has no readable expiry (not an AniList token?)".to_string())?;
+    let token = StoredToken {
+        access_token: access_token.to_string(),
+        refresh_token: None,
+        expires_at,
+        invalid: false,
+        user_id: None,
+    };
+    replace_token(Some(token.clone()))?;
+    Ok(token)
+}
+
+/// Forget the stored token (logout)
+pub fn clear_token() -> Result<(), String> {
+    replace_token(None)
+}
+
+/// The stored token, if it is still usable
+pub fn access_token() -> Option<String> {
+    let state = TOKEN.lock().ok()?;
+    let token = state.token.as_ref()?;
+    let usable = !token.invalid && token.expires_at > current_ts_secs();
+    usable.then(|| token.access_token.clone())
+}
+
+/// AniList user ID of the stored login, if it has been looked up
+pub fn user_id() -> Option<i32> {
+    TOKEN.lock().ok()?.token.as_ref()?.user_id
+}
+
+/// Remember whose token `access_token` is, if it is still the stored one
+pub fn set_user_id(access_token: &str, user_id: i32) {
+    let mut state = match TOKEN.lock() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+    let token = match state.token.as_mut() {
+        Some(token) if token.access_token == access_token => token,
+        _ => return,
+    };
+    token.user_id = Some(user_id);
+    if let Err(e) = save_token_file(Some(token)) {
+        eprintln!("[AniList Auth] {}", e);
+    }
+}
+
+/// Mark the stored token invalid after AniList rejected `access_token`
+///
+/// A rejected token that isn't the stored one (passed in by the frontend) is ignored.
+pub fn mark_invalid(access_token: &str) {
+    let mut state = match TOKEN.lock() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+    let token = match state.token.as_mut() {
+        Some(token) if token.access_token == access_token && !token.invalid => token,
+        _ => return,
+    };
+    token.invalid = true;
+    println!("[AniList Auth] Token rejected by AniList, re-auth required");
+    if let Err(e) = save_token_file(Some(token)) {
+        eprintln!("[AniList Auth] {}", e);
+    }
+    // The rejection is reported by the caller; the expiry watch stays quiet
+    let expires_at = token.expires_at;
+    state.notified = Some(TokenNotice::Expired { expires_at });
+}
+
+/// Current token state
+pub fn token_status() -> TokenStatus {
+    let token = TOKEN.lock().ok().and_then(|state| state.token.clone());
+    status_at(token.as_ref(), current_ts_secs())
+}
+
+/// A notice the user hasn't been given yet for the stored token
+pub fn check_expiry() -> Option<TokenNotice> {
+    let mut state = TOKEN.lock().ok()?;
+    let notice = notice_at(state.token.as_ref()?, current_ts_secs())?;
+    let already_sent = matches!(
+        (&state.notified, &notice),
+        (
+            Some(TokenNotice::Expiring { .. }),
+            TokenNotice::Expiring { .. }
+        ) | (Some(TokenNotice::Expired { .. }), _)
+    );
+    if already_sent {
+        return None;
+    }
+    state.notified = Some(notice.clone());
+    Some(notice)
+}
+
+fn status_at(token: Option<&StoredToken>, now: i64) -> TokenStatus {
+    let token = match token {
+        Some(token) => token,
+        None => {
+            return TokenStatus {
+                logged_in: false,
+                expires_at: None,
+                expires_in_secs: None,
+                expiring_soon: false,
+                needs_reauth: false,
+            }
+        }
+    };
+    let expires_in = token.expires_at - now;
+    TokenStatus {
+        logged_in: true,
+        expires_at: Some(token.expires_at),
+        expires_in_secs: Some(expires_in.max(0)),
+        expiring_soon: expires_in > 0 && expires_in <= EXPIRY_WARNING.as_secs() as i64,
+        needs_reauth: token.invalid || expires_in <= 0,
+    }
+}
+
+fn notice_at(token: &StoredToken, now: i64) -> Option<TokenNotice> {
+    let status = status_at(Some(token), now);
+    if status.needs_reauth {
+        Some(TokenNotice::Expired {
+            expires_at: token.expires_at,
+        })
+    } else if status.expiring_soon {
+        Some(TokenNotice::Expiring {
+            expires_at: token.expires_at,
+            expires_in_secs: token.expires_at - now,
+        })
+    } else {
+        None
+    }
+}
+
+/// Whether a failed AniList response means the token itself was refused
+///
+/// AniList sends 401 for a missing or malformed header, and 400 with an
+/// "Invalid token" GraphQL error for an expired or revoked one.
+pub fn is_token_rejection(status: u16, body: &str) -> bool {
+    if status == 401 {
+        return true;
+    }
+    let value: serde_json::Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let errors = value["errors"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    errors.iter().any(|error| {
+        let message = error["message"].as_str().unwrap_or_default().to_lowercase();
+        error["status"].as_u64() == Some(401)
+            || message.contains("invalid token")
+            || message.contains("unauthorized")
+    })
+}
+
+/// The `exp` claim of a JWT
+fn jwt_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims["exp"].as_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_at: i64) -> StoredToken {
+        StoredToken {
+            access_token: "token".to_string(),
+            refresh_token: None,
+            expires_at,
+            invalid: false,
+            user_id: None,
+        }
+    }
+
+    #[test]
+    fn test_expiry_notices() {
+        let day = 24 * 60 * 60;
+        let now = 1_700_000_000;
+        assert_eq!(notice_at(&token(now + 30 * day), now), None);
+        assert_eq!(
+            notice_at(&token(now + 2 * day), now),
+            Some(TokenNotice::Expiring {
+                expires_at: now + 2 * day,
+                expires_in_secs: 2 * day
+            })
+        );
+        assert_eq!(
+            notice_at(&token(now - 1), now),
+            Some(TokenNotice::Expired {
+                expires_at: now - 1
+            })
+        );
+
+        let mut rejected = token(now + 30 * day);
+        rejected.invalid = true;
+        let status = status_at(Some(&rejected), now);
+        assert!(status.logged_in && status.needs_reauth && !status.expiring_soon);
+        assert!(!status_at(None, now).logged_in);
+    }
+
+    #[test]
+    fn test_token_rejections() {
+        assert!(is_token_rejection(401, ""));
+        assert!(is_token_rejection(
+            400,
+            r#"{"errors":[{"message":"Invalid token","status":400}],"data":null}"#
+        ));
+        assert!(!is_token_rejection(
+            429,
+            r#"{"errors":[{"message":"Too Many Requests.","status":429}]}"#
+        ));
+        assert!(!is_token_rejection(500, "<html>Bad Gateway</html>"));
+    }
+
+    #[test]
+    fn test_jwt_expiry() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"aud":"1","exp":1735689600}"#);
+        assert_eq!(
+            jwt_expiry(&format!("e30.{}.sig", payload)),
+            Some(1735689600)
+        );
+        assert_eq!(jwt_expiry("not-a-jwt"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let path =
+            std::env::temp_dir().join(format!("anilist_token_test_{}.json", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"{}").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -39,36 +39,33 @@ pub async fn cast_discover() -> Result<Vec<CastDeviceInfo>, String> {
     let start = std::time::Instant::now();
 
     while start.elapsed() < Duration::from_secs(2) {
-        if let Ok(event) = receiver.recv_timeout(Duration::from_millis(100)) {
-            match event {
-                mdns_sd::ServiceEvent::ServiceResolved(info) => {
-                    println!(
-                        "[Casting] Found device: {} at {:?}",
-                        info.get_fullname(),
-                        info.get_addresses()
-                    );
-
-                    let name = info
-                        .get_property_val_str("fn")
-                        .unwrap_or_else(|| info.get_fullname())
-                        .to_string();
-
-                    if let Some(ip) = info.get_addresses().iter().find(|ip| ip.is_ipv4()) {
-                        let device_info = CastDeviceInfo {
-                            name,
-                            ip: ip.to_string(),
-                        };
-
-                        // We check uniqueness by IP because friendly names might change or be identical
-                        if !devices
-                            .iter()
-                            .any(|d: &CastDeviceInfo| d.ip == device_info.ip)
-                        {
-                            devices.push(device_info);
-                        }
-                    }
+        if let Ok(mdns_sd::ServiceEvent::ServiceResolved(info)) =
+            receiver.recv_timeout(Duration::from_millis(100))
+        {
+            println!(
+                "[Casting] Found device: {} at {:?}",
+                info.get_fullname(),
+                info.get_addresses()
+            );
+
+            let name = info
+                .get_property_val_str("fn")
+                .unwrap_or_else(|| info.get_fullname())
+                .to_string();
+
+            if let Some(ip) = info.get_addresses().iter().find(|ip| ip.is_ipv4()) {
+                let device_info = CastDeviceInfo {
+                    name,
+                    ip: ip.to_string(),
+                };
+
+                // We check uniqueness by IP because friendly names might change or be identical
+                if !devices
+                    .iter()
+                    .any(|d: &CastDeviceInfo| d.ip == device_info.ip)
+                {
+                    devices.push(device_info);
                 }
-                _ => {}
             }
         }
     }
@@ -196,8 +193,8 @@ pub async fn cast_load_media(
                     for (k, v) in h {
                         target_proxy_url.push_str(&format!(
                             "&{}={}",
-                            urlencoding::encode(&k),
-                            urlencoding::encode(&v)
+                            urlencoding::encode(k),
+                            urlencoding::encode(v)
                         ));
                     }
                 }
@@ -0,0 +1,364 @@
+//! Background Detection Module
+//!
+//! PURPOSE: Track what is playing from the backend so the frontend doesn't
+//! have to poll `detect_anime_command`
+//!
+//! APPROACH: A loop (started in `run().setup`) samples the detection sources
+//! every `interval_secs`. Samples go through a `Debouncer`, which only
+//! reports a change once it has been seen on `debounce_samples` consecutive
+//! samples. The loop emits events only on those transitions:
+//! - `media-detected` - something started playing
+//! - `media-changed`  - a different title/episode is now playing
+//! - `media-stopped`  - nothing is playing anymore
+//!
+//! Position updates for the same episode are not transitions; they only feed
+//! the scrobbler.
+//!
+//! After every sample the loop publishes a `Snapshot` (committed sample plus
+//! its match). `detect_anime_command` only reads it, so frontend polling
+//! never samples, looks titles up or feeds the scrobbler a second time.
+
+use crate::anilist::Anime;
+use crate::episode_mapper::MappedEpisode;
+use crate::media_player::{MediaPlayer, PlaybackInfo};
+use crate::title_parser::{MediaKind, ParseResult, ParsedTitle, ScoredParse, MIN_CONFIDENCE};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Loop settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DetectionConfig {
+    /// Seconds between samples (1 - `MAX_INTERVAL_SECS`)
+    pub interval_secs: u64,
+    /// Consecutive identical samples required before a change is reported
+    pub debounce_samples: u32,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        DetectionConfig {
+            interval_secs: 5,
+            debounce_samples: 2,
+        }
+    }
+}
+
+/// What the background loop currently reports
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    /// The debounced sample (None while nothing is playing)
+    pub media: Option<MediaSample>,
+    pub anilist_match: Option<Anime>,
+    pub episode_mapping: Option<MappedEpisode>,
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIG: Mutex<DetectionConfig> = Mutex::new(DetectionConfig::default());
+    static ref SNAPSHOT: Mutex<Snapshot> = Mutex::new(Snapshot::default());
+}
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn get_config() -> DetectionConfig {
+    CONFIG.lock().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Longest allowed sampling interval
+///
+/// The scrobbler drops watch time between samples further apart than
+/// `MAX_SAMPLE_GAP`, and sampling plus lookups add to the interval, so stay
+/// well below it or nothing would ever be scrobbled.
+pub const MAX_INTERVAL_SECS: u64 = crate::scrobbler::MAX_SAMPLE_GAP.as_secs() / 2;
+
+/// Check a config from the frontend, filling in the debounce minimum
+fn validate_config(config: DetectionConfig) -> Result<DetectionConfig, String> {
+    if !(1..=MAX_INTERVAL_SECS).contains(&config.interval_secs) {
+        return Err(format!(
+            "interval_secs must be between 1 and {}, got {}",
+            MAX_INTERVAL_SECS, config.interval_secs
+        ));
+    }
+    Ok(DetectionConfig {
+        debounce_samples: config.debounce_samples.max(1),
+        ..config
+    })
+}
+
+pub fn set_config(config: DetectionConfig) -> Result<(), String> {
+    let config = validate_config(config)?;
+    let mut current = CONFIG.lock().map_err(|_| "Config lock error")?;
+    *current = config;
+    Ok(())
+}
+
+/// Latest state published by the detection loop
+pub fn snapshot() -> Snapshot {
+    SNAPSHOT.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+pub fn publish(snapshot: Snapshot) {
+    if let Ok(mut current) = SNAPSHOT.lock() {
+        *current = snapshot;
+    }
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// One sample of what a media player is showing (before any AniList lookup)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaSample {
+    pub player: MediaPlayer,
+    /// Detection rule that matched the window (None for playback sources)
+    pub matched_rule: Option<String>,
+    pub window_title: String,
+    pub parsed: ParsedTitle,
+    /// Parser confidence in `parsed` (0.0 - 1.0)
+    pub confidence: f64,
+    /// Other readings of the same title, most confident first
+    pub alternatives: Vec<ScoredParse>,
+    pub playback: Option<PlaybackInfo>,
+}
+
+impl MediaSample {
+    pub fn new(
+        player: MediaPlayer,
+        matched_rule: Option<String>,
+        window_title: String,
+        parse: ParseResult,
+        playback: Option<PlaybackInfo>,
+    ) -> Self {
+        MediaSample {
+            player,
+            matched_rule,
+            window_title,
+            parsed: parse.best.parsed,
+            confidence: parse.best.confidence,
+            alternatives: parse.alternatives,
+            playback,
+        }
+    }
+
+    /// Name of the folder holding the playing file (local playback only)
+    pub fn folder(&self) -> Option<String> {
+        let path = self.playback.as_ref()?.local_path()?;
+        let mut parts = path.split(['/', '\\']).filter(|p| !p.trim().is_empty());
+        parts.next_back();
+        parts.next_back().map(String::from)
+    }
+
+    /// Low-confidence parses are shown to the user but never matched or scrobbled
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= MIN_CONFIDENCE
+    }
+
+    /// Identity used for change detection: position and play state are ignored
+    fn key(&self) -> (Option<String>, Option<i32>, Option<i32>, MediaKind) {
+        (
+            self.parsed.title.as_ref().map(|t| t.to_lowercase()),
+            self.parsed.season,
+            self.parsed.episode,
+            self.parsed.kind,
+        )
+    }
+}
+
+/// A debounced change in what is playing
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    Detected(MediaSample),
+    Changed {
+        previous: Box<MediaSample>,
+        current: Box<MediaSample>,
+    },
+    Stopped(MediaSample),
+}
+
+impl Transition {
+    /// Tauri event name for this transition
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            Transition::Detected(_) => "media-detected",
+            Transition::Changed { .. } => "media-changed",
+            Transition::Stopped(_) => "media-stopped",
+        }
+    }
+
+    /// Event payload: `{ media, previous, anilist_match }`
+    pub fn payload(&self, anilist_match: Option<&Anime>) -> serde_json::Value {
+        let (media, previous) = match self {
+            Transition::Detected(current) => (Some(current), None),
+            Transition::Changed { previous, current } => {
+                (Some(current.as_ref()), Some(previous.as_ref()))
+            }
+            Transition::Stopped(previous) => (None, Some(previous)),
+        };
+
+        serde_json::json!({
+            "media": media,
+            "previous": previous,
+            "anilist_match": anilist_match
+        })
+    }
+}
+
+/// Turns a stream of samples into transitions, ignoring one-off flickers
+/// (e.g. a player briefly showing its bare name while switching files)
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    stable: Option<MediaSample>,
+    pending: Option<Option<MediaSample>>,
+    pending_count: u32,
+}
+
+impl Debouncer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last committed sample
+    pub fn current(&self) -> Option<&MediaSample> {
+        self.stable.as_ref()
+    }
+
+    /// Forget the committed state, reporting Stopped if something was playing
+    pub fn reset(&mut self) -> Option<Transition> {
+        self.pending = None;
+        self.pending_count = 0;
+        self.stable.take().map(Transition::Stopped)
+    }
+
+    /// Feed one sample; returns a transition once a change has held for `required` samples
+    pub fn update(&mut self, sample: Option<MediaSample>, required: u32) -> Option<Transition> {
+        let key = sample.as_ref().map(MediaSample::key);
+
+        if key == self.stable.as_ref().map(MediaSample::key) {
+            // Same media: keep the latest playback position
+            self.stable = sample;
+            self.pending = None;
+            self.pending_count = 0;
+            return None;
+        }
+
+        let same_pending = self
+            .pending
+            .as_ref()
+            .is_some_and(|p| p.as_ref().map(MediaSample::key) == key);
+        if same_pending {
+            self.pending_count += 1;
+        } else {
+            self.pending_count = 1;
+        }
+        self.pending = Some(sample);
+
+        if self.pending_count < required.max(1) {
+            return None;
+        }
+
+        let next = self.pending.take().flatten();
+        self.pending_count = 0;
+        let previous = std::mem::replace(&mut self.stable, next.clone());
+
+        match (previous, next) {
+            (None, Some(current)) => Some(Transition::Detected(current)),
+            (Some(previous), Some(current)) => Some(Transition::Changed {
+                previous: Box::new(previous),
+                current: Box::new(current),
+            }),
+            (Some(previous), None) => Some(Transition::Stopped(previous)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::title_parser::parse_window_title_ranked;
+
+    fn sample(title: &str) -> MediaSample {
+        MediaSample::new(
+            MediaPlayer::VLC,
+            Some("vlc".to_string()),
+            title.to_string(),
+            parse_window_title_ranked(title),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_detect_change_stop() {
+        let mut debouncer = Debouncer::new();
+        let ep5 = sample("Frieren - 05.mkv - VLC media player");
+        let ep6 = sample("Frieren - 06.mkv - VLC media player");
+
+        assert_eq!(debouncer.update(Some(ep5.clone()), 2), None);
+        assert_eq!(
+            debouncer.update(Some(ep5.clone()), 2),
+            Some(Transition::Detected(ep5.clone()))
+        );
+        assert_eq!(debouncer.update(Some(ep5.clone()), 2), None);
+
+        assert_eq!(debouncer.update(Some(ep6.clone()), 2), None);
+        assert_eq!(
+            debouncer.update(Some(ep6.clone()), 2),
+            Some(Transition::Changed {
+                previous: Box::new(ep5),
+                current: Box::new(ep6.clone())
+            })
+        );
+
+        assert_eq!(debouncer.update(None, 2), None);
+        assert_eq!(debouncer.update(None, 2), Some(Transition::Stopped(ep6)));
+        assert_eq!(debouncer.update(None, 2), None);
+    }
+
+    #[test]
+    fn test_flicker_is_ignored() {
+        let mut debouncer = Debouncer::new();
+        let ep5 = sample("Frieren - 05.mkv - VLC media player");
+
+        debouncer.update(Some(ep5.clone()), 2);
+        debouncer.update(Some(ep5.clone()), 2);
+
+        // A single empty sample between two matching ones is not a stop
+        assert_eq!(debouncer.update(None, 2), None);
+        assert_eq!(debouncer.update(Some(ep5.clone()), 2), None);
+        assert_eq!(debouncer.update(None, 2), None);
+        assert_eq!(debouncer.current(), Some(&ep5));
+    }
+
+    #[test]
+    fn test_reset_reports_stop() {
+        let mut debouncer = Debouncer::new();
+        let ep5 = sample("Frieren - 05.mkv - VLC media player");
+
+        assert_eq!(
+            debouncer.update(Some(ep5.clone()), 1),
+            Some(Transition::Detected(ep5.clone()))
+        );
+        assert_eq!(debouncer.reset(), Some(Transition::Stopped(ep5)));
+        assert_eq!(debouncer.reset(), None);
+    }
+
+    #[test]
+    fn test_interval_must_stay_below_scrobble_gap() {
+        let config = |interval_secs| DetectionConfig {
+            interval_secs,
+            debounce_samples: 0,
+        };
+
+        assert!(validate_config(config(0)).is_err());
+        assert!(validate_config(config(120)).is_err());
+
+        let accepted = validate_config(config(MAX_INTERVAL_SECS)).unwrap();
+        assert_eq!(accepted.interval_secs, MAX_INTERVAL_SECS);
+        assert_eq!(accepted.debounce_samples, 1);
+    }
+}
@@ -0,0 +1,259 @@
+//! Episode Mapper Module
+//!
+//! PURPOSE: Move absolute episode numbers (`One Punch Man - 15`) and
+//! season-numbered ones (`S02E03`) to the AniList entry of their season,
+//! since AniList gives every season its own media ID
+//!
+//! APPROACH: Walk PREQUEL relations back to the first season, then SEQUEL
+//! relations forward, keeping only series entries (TV, TV_SHORT, ONA) so
+//! movies and OVAs don't count as seasons. A season number picks an entry
+//! of that chain; episodes past an entry's count carry over into its
+//! sequels. Relation nodes come from `anilist::get_media_relations`, which
+//! keeps them in the AniList SQLite cache.
+
+use crate::anilist::{self, Anime, MediaRelations};
+use crate::title_parser::{EpisodeRange, ParsedTitle};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Most entries walked in either direction from the matched one
+const MAX_SEASONS: usize = 12;
+
+/// Formats that count as a season of a series
+const SEASON_FORMATS: [&str; 3] = ["TV", "TV_SHORT", "ONA"];
+
+/// One season of a franchise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeasonEntry {
+    pub id: i32,
+    /// Episode count (None while airing)
+    pub episodes: Option<i32>,
+}
+
+/// Where a parsed episode belongs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MappedEpisode {
+    /// AniList ID of the season entry
+    pub media_id: i32,
+    /// Episode number within that season
+    pub episode: i32,
+    /// Episodes of earlier seasons subtracted from the parsed number
+    pub offset: i32,
+}
+
+impl MappedEpisode {
+    /// The parse with its episode numbers moved into the mapped season
+    pub fn apply(&self, parsed: &ParsedTitle) -> ParsedTitle {
+        ParsedTitle {
+            episode: Some(self.episode),
+            episode_range: parsed.episode_range.map(|range| EpisodeRange {
+                start: (range.start - self.offset).max(1),
+                end: range.end - self.offset,
+            }),
+            ..parsed.clone()
+        }
+    }
+}
+
+/// Map an episode onto a franchise's seasons, first season first
+///
+/// `matched` is the index of the entry the title search found. A `season`
+/// counts from the first entry; without one the episode is read relative to
+/// the matched entry, or as absolute when it is past that entry's count.
+///
+/// Returns None when the season isn't in the chain (or is 0, i.e. specials).
+pub fn map_in_seasons(
+    seasons: &[SeasonEntry],
+    matched: usize,
+    season: Option<i32>,
+    episode: i32,
+) -> Option<MappedEpisode> {
+    let mut index = match season {
+        Some(season) if season < 1 => return None,
+        Some(season) => (season - 1) as usize,
+        None => matched,
+    };
+    let entry = seasons.get(index)?;
+    let mut offset = 0;
+
+    // "Season 2" matched but the number counts from the first season
+    if season.is_none() && index > 0 && entry.episodes.is_some_and(|n| episode > n) {
+        let before: Option<i32> = seasons[..index].iter().map(|s| s.episodes).sum();
+        if let Some(before) = before.filter(|&before| episode > before) {
+            offset = before;
+        }
+    }
+
+    // Episodes past a season's end carry over into its sequels
+    while let Some(count) = seasons[index].episodes {
+        if episode - offset <= count || index + 1 >= seasons.len() {
+            break;
+        }
+        offset += count;
+        index += 1;
+    }
+
+    Some(MappedEpisode {
+        media_id: seasons[index].id,
+        episode: episode - offset,
+        offset,
+    })
+}
+
+/// Find the season entry and relative episode for a matched anime
+///
+/// # Arguments
+/// * `anime` - The entry the title search matched
+/// * `season` - Parsed season number (`S02E03` → 2), if any
+/// * `episode` - Parsed episode number
+///
+/// # Returns
+/// * `Ok(None)` when the episode already belongs to `anime`
+pub async fn map_episode(
+    anime: &Anime,
+    season: Option<i32>,
+    episode: i32,
+) -> Result<Option<MappedEpisode>, String> {
+    let is_series = anime
+        .format
+        .as_deref()
+        .is_some_and(|f| SEASON_FORMATS.contains(&f));
+    if !is_series || season == Some(0) {
+        return Ok(None);
+    }
+
+    // A first-season episode within the entry's count needs no lookup
+    let in_entry = anime.episodes.is_none_or(|n| episode <= n);
+    if season.unwrap_or(1) == 1 && in_entry {
+        return Ok(None);
+    }
+
+    let (seasons, matched) = season_chain(anime.id).await?;
+    let mapped = map_in_seasons(&seasons, matched, season, episode);
+    if let Some(m) = mapped.as_ref() {
+        println!(
+            "[Episodes] S{:?}E{} of {} → episode {} of {}",
+            season, episode, anime.id, m.episode, m.media_id
+        );
+    }
+    Ok(mapped.filter(|m| m.media_id != anime.id || m.episode != episode))
+}
+
+/// The seasons of `id`'s franchise in airing order, and the index of `id`
+async fn season_chain(id: i32) -> Result<(Vec<SeasonEntry>, usize), String> {
+    let start = anilist::get_media_relations(id).await?;
+    let mut seen = HashSet::from([start.id]);
+
+    let mut seasons = walk(&start, "PREQUEL", &mut seen).await?;
+    seasons.reverse();
+    let matched = seasons.len();
+    seasons.push(season_entry(&start));
+    seasons.extend(walk(&start, "SEQUEL", &mut seen).await?);
+
+    Ok((seasons, matched))
+}
+
+/// Follow one relation type from `node` until it runs out (or loops)
+async fn walk(
+    node: &MediaRelations,
+    relation: &str,
+    seen: &mut HashSet<i32>,
+) -> Result<Vec<SeasonEntry>, String> {
+    let mut entries = Vec::new();
+    let mut next = next_season(node, relation);
+
+    while let Some(id) = next.filter(|id| seen.insert(*id)) {
+        if entries.len() >= MAX_SEASONS {
+            break;
+        }
+        let node = anilist::get_media_relations(id).await?;
+        entries.push(season_entry(&node));
+        next = next_season(&node, relation);
+    }
+    Ok(entries)
+}
+
+/// The related series entry of the given relation type (SEQUEL/PREQUEL)
+fn next_season(node: &MediaRelations, relation: &str) -> Option<i32> {
+    node.relations
+        .iter()
+        .find(|r| {
+            r.relation_type == relation
+                && r.format
+                    .as_deref()
+                    .is_some_and(|f| SEASON_FORMATS.contains(&f))
+        })
+        .map(|r| r.id)
+}
+
+fn season_entry(node: &MediaRelations) -> SeasonEntry {
+    SeasonEntry {
+        id: node.id,
+        episodes: node.episodes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One Punch Man: two 12-episode seasons and an airing third
+    const OPM: [SeasonEntry; 3] = [
+        SeasonEntry {
+            id: 21087,
+            episodes: Some(12),
+        },
+        SeasonEntry {
+            id: 97668,
+            episodes: Some(12),
+        },
+        SeasonEntry {
+            id: 153800,
+            episodes: None,
+        },
+    ];
+
+    fn mapped(media_id: i32, episode: i32, offset: i32) -> Option<MappedEpisode> {
+        Some(MappedEpisode {
+            media_id,
+            episode,
+            offset,
+        })
+    }
+
+    #[test]
+    fn test_absolute_episodes() {
+        assert_eq!(map_in_seasons(&OPM, 0, None, 5), mapped(21087, 5, 0));
+        assert_eq!(map_in_seasons(&OPM, 0, None, 15), mapped(97668, 3, 12));
+        // The airing season takes everything past the finished ones
+        assert_eq!(map_in_seasons(&OPM, 0, None, 30), mapped(153800, 6, 24));
+        // Search found season 2 but the file counts from season 1
+        assert_eq!(map_in_seasons(&OPM, 1, None, 15), mapped(97668, 3, 12));
+        assert_eq!(map_in_seasons(&OPM, 1, None, 4), mapped(97668, 4, 0));
+    }
+
+    #[test]
+    fn test_season_numbers() {
+        assert_eq!(map_in_seasons(&OPM, 0, Some(2), 3), mapped(97668, 3, 0));
+        // "S02E15": season-2 entry runs out, the rest is season 3
+        assert_eq!(map_in_seasons(&OPM, 0, Some(2), 15), mapped(153800, 3, 12));
+        assert_eq!(map_in_seasons(&OPM, 0, Some(4), 1), None);
+        assert_eq!(map_in_seasons(&OPM, 0, Some(0), 1), None);
+    }
+
+    #[test]
+    fn test_apply_shifts_ranges() {
+        let parsed = ParsedTitle {
+            title: Some("One Punch Man".to_string()),
+            episode: Some(15),
+            episode_range: Some(EpisodeRange { start: 13, end: 15 }),
+            ..Default::default()
+        };
+        let parsed = mapped(97668, 3, 12).unwrap().apply(&parsed);
+        assert_eq!(parsed.episode, Some(3));
+        assert_eq!(
+            parsed.episode_range,
+            Some(EpisodeRange { start: 1, end: 3 })
+        );
+    }
+}
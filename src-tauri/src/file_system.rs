@@ -22,60 +22,58 @@ pub fn get_folder_contents(path: String) -> Result<Vec<FileItem>, String> {
     let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
     let mut files = Vec::new();
 
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path_buf = entry.path();
-            let is_dir = path_buf.is_dir();
-            let name = entry.file_name().to_string_lossy().to_string();
+    for entry in entries.flatten() {
+        let path_buf = entry.path();
+        let is_dir = path_buf.is_dir();
+        let name = entry.file_name().to_string_lossy().to_string();
 
-            // Basic filtering for hidden files
-            if name.starts_with('.') {
-                continue;
-            }
+        // Basic filtering for hidden files
+        if name.starts_with('.') {
+            continue;
+        }
 
-            let metadata = entry.metadata().ok();
-            let size = if !is_dir {
-                metadata.as_ref().map(|m| m.len())
-            } else {
-                None
-            };
+        let metadata = entry.metadata().ok();
+        let size = if !is_dir {
+            metadata.as_ref().map(|m| m.len())
+        } else {
+            None
+        };
 
-            let last_modified = metadata
-                .as_ref()
-                .and_then(|m| m.modified().ok())
-                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs());
+        let last_modified = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
 
-            // If it's a file, filter by video extensions
-            if !is_dir {
-                if let Some(ext) = path_buf.extension() {
-                    let ext_str = ext.to_string_lossy().to_lowercase();
-                    match ext_str.as_str() {
-                        // Video files
-                        "mp4" | "mkv" | "avi" | "mov" | "webm" | "flv" | "wmv" |
-                        // Manga/Comic files
-                        "pdf" | "cbz" | "cbr" => {
-                            files.push(FileItem {
-                                name,
-                                path: path_buf.to_string_lossy().to_string(),
-                                is_dir,
-                                size,
-                                last_modified,
-                            });
-                        }
-                        _ => continue, // Skip other files
+        // If it's a file, filter by video extensions
+        if !is_dir {
+            if let Some(ext) = path_buf.extension() {
+                let ext_str = ext.to_string_lossy().to_lowercase();
+                match ext_str.as_str() {
+                    // Video files
+                    "mp4" | "mkv" | "avi" | "mov" | "webm" | "flv" | "wmv" |
+                    // Manga/Comic files
+                    "pdf" | "cbz" | "cbr" => {
+                        files.push(FileItem {
+                            name,
+                            path: path_buf.to_string_lossy().to_string(),
+                            is_dir,
+                            size,
+                            last_modified,
+                        });
                     }
+                    _ => continue, // Skip other files
                 }
-            } else {
-                // Always include directories
-                files.push(FileItem {
-                    name,
-                    path: path_buf.to_string_lossy().to_string(),
-                    is_dir,
-                    size,
-                    last_modified,
-                });
             }
+        } else {
+            // Always include directories
+            files.push(FileItem {
+                name,
+                path: path_buf.to_string_lossy().to_string(),
+                is_dir,
+                size,
+                last_modified,
+            });
         }
     }
 
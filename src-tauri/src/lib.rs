@@ -7,6 +7,13 @@ mod win_name;
 #[cfg(target_os = "macos")]
 mod mac_name;
 
+#[cfg(target_os = "linux")]
+mod linux_name;
+
+// MPRIS now-playing source (Linux D-Bus)
+#[cfg(target_os = "linux")]
+mod mpris;
+
 // Import the media_player module
 mod media_player;
 // Import the anilist module
@@ -25,6 +32,30 @@ mod myanimelist;
 mod casting;
 // Import stream server module
 mod stream_server;
+// Import mpv IPC client module
+mod mpv_ipc;
+// Import VLC / MPC-HC web interface module
+mod web_players;
+// Import watch-completion scrobbler module
+mod scrobbler;
+// Import background detection loop module
+mod detection;
+// Import streaming site title grammars module
+mod site_grammars;
+// Import user parsing rules module
+mod parsing_rules;
+// Import title tokenizer module
+mod title_tokenizer;
+// Import manga file name parser module
+mod manga_parser;
+// Import absolute-to-seasonal episode mapper module
+mod episode_mapper;
+// Import AniList candidate ranking module
+mod title_matcher;
+// Import learned title aliases module
+mod title_aliases;
+// Import AniList token lifecycle module
+mod anilist_auth;
 
 // Platform-conditional imports for unified interface
 #[cfg(windows)]
@@ -33,10 +64,13 @@ use win_name as platform_window;
 #[cfg(target_os = "macos")]
 use mac_name as platform_window;
 
+#[cfg(target_os = "linux")]
+use linux_name as platform_window;
+
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
 };
 use tauri::{Emitter, Manager};
@@ -68,6 +102,33 @@ async fn get_anime_by_id_command(id: i32) -> Result<String, String> {
     serde_json::to_string(&anime).map_err(|e| format!("Serialization error: {}", e))
 }
 
+/// Tauri command to search for manga on AniList
+///
+/// # Arguments
+/// * `query` - Search query (manga title)
+/// * `limit` - Maximum number of results (default: 10)
+///
+/// # Returns
+/// * JSON string with array of manga results
+#[tauri::command]
+async fn search_manga_command(query: String, limit: Option<i32>) -> Result<String, String> {
+    let results = anilist::search_manga(&query, limit.unwrap_or(10)).await?;
+    serde_json::to_string(&results).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to get manga details (chapters, volumes, status) by ID
+///
+/// # Arguments
+/// * `id` - AniList manga ID
+///
+/// # Returns
+/// * JSON string with manga details
+#[tauri::command]
+async fn get_manga_by_id_command(id: i32) -> Result<String, String> {
+    let manga = anilist::get_manga_by_id(id).await?;
+    serde_json::to_string(&manga).map_err(|e| format!("Serialization error: {}", e))
+}
+
 /// Tauri command to match anime from window title
 /// This combines media detection with AniList search
 ///
@@ -75,19 +136,19 @@ async fn get_anime_by_id_command(id: i32) -> Result<String, String> {
 /// * JSON string with matched anime or null if no match
 #[tauri::command]
 async fn match_anime_from_window_command() -> Result<String, String> {
-    // Get active window title
-    let title = match platform_window::get_active_window_title() {
-        Some(t) => t,
+    // Get active window
+    let window = match platform_window::get_active_window_info() {
+        Some(w) => w,
         None => return Ok("null".to_string()),
     };
 
     // Check if it's a media player
-    if media_player::detect_media_player(&title).is_none() {
+    if media_player::match_media_player(&window).is_none() {
         return Ok("null".to_string());
     }
 
     // Try to match with AniList
-    let anime = anilist::match_anime_from_title(&title).await?;
+    let anime = anilist::match_anime_from_title(&window.title).await?;
     serde_json::to_string(&anime).map_err(|e| format!("Serialization error: {}", e))
 }
 
@@ -108,13 +169,13 @@ fn get_active_window() -> String {
 /// Only returns data for known media players
 #[tauri::command]
 fn get_active_media_window() -> String {
-    use media_player::detect_media_player;
+    use media_player::match_media_player;
 
-    // Get active window title
-    let title = match platform_window::get_active_window_title() {
-        Some(t) => {
-            println!("[DEBUG] Active window title: {:?}", t);
-            t
+    // Get active window
+    let window = match platform_window::get_active_window_info() {
+        Some(w) => {
+            println!("[DEBUG] Active window: {:?}", w);
+            w
         }
         None => {
             println!("[DEBUG] No active window found");
@@ -123,20 +184,25 @@ fn get_active_media_window() -> String {
     };
 
     // Check if it's a media player
-    match detect_media_player(&title) {
-        Some(player) => {
-            println!("[DEBUG] Detected media player: {:?}", player);
+    match match_media_player(&window) {
+        Some(found) => {
+            println!(
+                "[DEBUG] Detected media player: {:?} (rule '{}' on {:?})",
+                found.player, found.rule, found.matched_on
+            );
             // Return structured info
-            format!("{:?}: {}", player, title)
+            format!("{:?}: {}", found.player, window.title)
         }
         None => {
-            println!("[DEBUG] Not a media player: {}", title);
+            println!("[DEBUG] Not a media player: {}", window.title);
             // Not a media player - ignore
             "No media playing".to_string()
         }
     }
 }
 
+/// Tauri command to exchange an AniList OAuth code for a token
+/// The token is also stored with its expiry for background updates
 #[tauri::command]
 async fn exchange_login_code(
     code: String,
@@ -146,9 +212,81 @@ async fn exchange_login_code(
 ) -> Result<String, String> {
     let token_data =
         anilist::exchange_code_for_token(code, client_id, client_secret, redirect_uri).await?;
+    if let Err(e) = anilist_auth::store_token_response(&token_data) {
+        eprintln!("[AniList Auth] {}", e);
+    }
     serde_json::to_string(&token_data).map_err(|e| format!("Serialization error: {}", e))
 }
 
+/// Tauri command to hand the backend an AniList token the frontend already holds
+/// (logins from before the backend stored tokens)
+///
+/// # Returns
+/// * JSON string with the token status
+#[tauri::command]
+fn set_anilist_token(access_token: String) -> Result<String, String> {
+    anilist_auth::store_access_token(&access_token)?;
+    serde_json::to_string(&anilist_auth::token_status())
+        .map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to forget the stored AniList token (logout)
+#[tauri::command]
+fn clear_anilist_token() -> Result<(), String> {
+    anilist_auth::clear_token()
+}
+
+/// Tauri command to get the stored AniList token's state
+/// (logged in, expiry, whether a new login is needed)
+#[tauri::command]
+fn get_anilist_token_status() -> String {
+    serde_json::to_string(&anilist_auth::token_status()).unwrap_or_else(|_| "null".to_string())
+}
+
+/// Tell the frontend the AniList login has to be redone
+fn emit_reauth_required(app: &tauri::AppHandle, reason: &str) {
+    use serde_json::json;
+
+    println!("[AniList Auth] Re-auth required: {}", reason);
+    let _ = app.emit(
+        "anilist-reauth-required",
+        json!({ "reason": reason, "status": anilist_auth::token_status() }),
+    );
+}
+
+/// The token an AniList command should use: the one passed in, else the stored login
+fn anilist_token(access_token: Option<String>) -> Result<String, String> {
+    access_token
+        .or_else(anilist_auth::access_token)
+        .ok_or_else(|| "Not logged in to AniList".to_string())
+}
+
+/// Emit `anilist-reauth-required` when a request failed on a refused token
+fn check_reauth<T>(app: &tauri::AppHandle, result: &Result<T, anilist::AniListError>) {
+    if let Err(anilist::AniListError::InvalidToken(reason)) = result {
+        emit_reauth_required(app, reason);
+    }
+}
+
+/// Watch the stored AniList token's expiry
+/// Emits `anilist-token-expiring` once it is close to expiring and
+/// `anilist-reauth-required` once it has expired
+async fn run_token_watch(app: tauri::AppHandle) {
+    loop {
+        match anilist_auth::check_expiry() {
+            Some(notice @ anilist_auth::TokenNotice::Expiring { .. }) => {
+                println!("[AniList Auth] Token expiring soon: {:?}", notice);
+                let _ = app.emit("anilist-token-expiring", &notice);
+            }
+            Some(anilist_auth::TokenNotice::Expired { .. }) => {
+                emit_reauth_required(&app, "expired");
+            }
+            None => {}
+        }
+        tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    }
+}
+
 /// Tauri command to parse a window title and extract anime info
 ///
 /// # Arguments
@@ -162,6 +300,32 @@ fn parse_window_title_command(window_title: String) -> String {
     serde_json::to_string(&parsed).unwrap_or_else(|_| "null".to_string())
 }
 
+/// Tauri command to parse a full file path, using folder names as hints
+///
+/// # Arguments
+/// * `path` - Path of a video file (e.g. `/Anime/Frieren/Season 2/05.mkv`)
+///
+/// # Returns
+/// * JSON string with parsed title, episode, and season
+#[tauri::command]
+fn parse_media_path_command(path: String) -> String {
+    let parsed = title_parser::parse_media_path(&path);
+    serde_json::to_string(&parsed).unwrap_or_else(|_| "null".to_string())
+}
+
+/// Tauri command to parse a manga archive path (CBZ/CBR)
+///
+/// # Arguments
+/// * `path` - Path or file name of a manga archive (e.g. `/Manga/Frieren/Chapter 12.cbz`)
+///
+/// # Returns
+/// * JSON string with parsed series, volume, chapter, part and group
+#[tauri::command]
+fn parse_manga_path_command(path: String) -> String {
+    let parsed = manga_parser::parse_manga_path(&path);
+    serde_json::to_string(&parsed).unwrap_or_else(|_| "null".to_string())
+}
+
 /// Simple in-memory cache for AniList lookups
 /// This prevents hammering the API with repeated lookups for the same title
 use std::collections::HashMap;
@@ -169,7 +333,7 @@ use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 struct CacheEntry {
-    anime: Option<anilist::Anime>,
+    candidates: Vec<anilist::Anime>,
     timestamp: Instant,
 }
 
@@ -179,189 +343,802 @@ lazy_static::lazy_static! {
 
 const CACHE_DURATION: Duration = Duration::from_secs(300); // 5 minutes
 
-fn get_cached_anime(title: &str) -> Option<Option<anilist::Anime>> {
+fn get_cached_candidates(key: &str) -> Option<Vec<anilist::Anime>> {
     let cache = ANILIST_CACHE.lock().ok()?;
-    if let Some(entry) = cache.get(title) {
+    if let Some(entry) = cache.get(key) {
         if entry.timestamp.elapsed() < CACHE_DURATION {
-            return Some(entry.anime.clone());
+            return Some(entry.candidates.clone());
         }
     }
     None
 }
 
-fn set_cached_anime(title: String, anime: Option<anilist::Anime>) {
+fn set_cached_candidates(key: String, candidates: Vec<anilist::Anime>) {
     if let Ok(mut cache) = ANILIST_CACHE.lock() {
         cache.insert(
-            title,
+            key,
             CacheEntry {
-                anime,
+                candidates,
                 timestamp: Instant::now(),
             },
         );
     }
 }
 
-/// Tauri command to detect anime from the current media player window
-/// Combines: media detection → title parsing → AniList search (with caching)
+/// Read now-playing info straight from player integrations
+/// Returns the first source that reports a playing video file, or None
+async fn read_playback_sources() -> Option<media_player::PlaybackInfo> {
+    // A source without a video file (music, web page) must not hide the next one
+    let has_target = |info: &media_player::PlaybackInfo| info.parse_target().is_some();
+
+    // mpv IPC is the most precise source when the user has enabled it
+    if let Some(info) = mpv_ipc::get_now_playing().filter(has_target) {
+        return Some(info);
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(info) = mpris::get_now_playing().await {
+        return Some(info);
+    }
+
+    web_players::get_now_playing().await.filter(has_target)
+}
+
+/// Directory for PLAY-ON's persistent data
+/// `%LOCALAPPDATA%\PLAY-ON` on Windows, `~/.play-on` elsewhere, temp dir as a last resort
+pub(crate) fn app_data_dir() -> std::path::PathBuf {
+    use std::path::PathBuf;
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
+            let dir = PathBuf::from(local_app_data).join("PLAY-ON");
+            let _ = std::fs::create_dir_all(&dir);
+            return dir;
+        }
+    }
+
+    if let Ok(home_dir) = std::env::var("HOME") {
+        let dir = PathBuf::from(home_dir).join(".play-on");
+        let _ = std::fs::create_dir_all(&dir);
+        return dir;
+    }
+
+    let dir = std::env::temp_dir().join("playon");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Path of the user's media player detection rules
+fn player_rules_path() -> std::path::PathBuf {
+    app_data_dir().join("player_rules.json")
+}
+
+/// Tauri command to reload media player rules from `player_rules.json`
 ///
 /// # Returns
-/// * JSON with detected anime info including parsed title, episode, and matched AniList entry
+/// * Number of active rules, or the validation error (previous rules stay active)
+#[tauri::command]
+fn reload_player_rules() -> Result<usize, String> {
+    media_player::load_rules_file(&player_rules_path())
+}
+
+/// Tauri command to list the active media player rules
+#[tauri::command]
+fn get_player_rules() -> Vec<media_player::PlayerRule> {
+    media_player::get_rules()
+}
+
+/// Path of the user's title parsing rules
+fn parsing_rules_path() -> std::path::PathBuf {
+    app_data_dir().join("parsing_rules.json")
+}
+
+/// Tauri command to reload title parsing rules from `parsing_rules.json`
+///
+/// # Returns
+/// * Number of rule entries, or the validation error (previous rules stay active)
+#[tauri::command]
+fn reload_parsing_rules() -> Result<usize, String> {
+    parsing_rules::load_rules_file(&parsing_rules_path())
+}
+
+/// Tauri command to validate `parsing_rules.json` without applying it
+///
+/// # Returns
+/// * The rules as they would be loaded, or the first error found
+#[tauri::command]
+fn check_parsing_rules() -> Result<parsing_rules::ParsingRules, String> {
+    parsing_rules::check_rules_file(&parsing_rules_path())
+}
+
+/// Tauri command to teach detection the right entry for a title
+/// Called when the user corrects a wrong match; replaces the alias with the
+/// same title, release group and folder
+///
+/// # Returns
+/// * JSON string with the stored alias
 #[tauri::command]
-async fn detect_anime_command() -> Result<String, String> {
+fn add_title_alias(alias: title_aliases::TitleAlias) -> Result<String, String> {
+    let stored = title_aliases::add_alias(&alias)?;
+    serde_json::to_string(&stored).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to list the learned title aliases
+///
+/// # Returns
+/// * JSON array of aliases, sorted by title
+#[tauri::command]
+fn list_title_aliases() -> Result<String, String> {
+    let aliases = title_aliases::list_aliases()?;
+    serde_json::to_string(&aliases).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to remove a title alias by ID
+///
+/// # Returns
+/// * Whether an alias was removed
+#[tauri::command]
+fn remove_title_alias(id: i64) -> Result<bool, String> {
+    title_aliases::remove_alias(id)
+}
+
+/// Tauri command to export all title aliases as a JSON array
+#[tauri::command]
+fn export_title_aliases() -> Result<String, String> {
+    title_aliases::export_aliases()
+}
+
+/// Tauri command to import title aliases from an exported JSON array
+///
+/// # Returns
+/// * Number of aliases imported (none when any entry is invalid)
+#[tauri::command]
+fn import_title_aliases(json: String) -> Result<usize, String> {
+    title_aliases::import_aliases(&json)
+}
+
+/// Tauri command to get the active title parsing rules
+#[tauri::command]
+fn get_parsing_rules() -> parsing_rules::ParsingRules {
+    parsing_rules::get_rules()
+}
+
+/// Modification time of a rules file (None when it doesn't exist)
+fn rules_file_modified(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload `player_rules.json` and `parsing_rules.json` whenever they change
+///
+/// Emits `rules-reloaded` (`{ file, count }`) or `rules-error` (`{ file, error }`);
+/// on error the previous rules stay active.
+async fn run_rules_watcher(app: tauri::AppHandle) {
+    let player_path = player_rules_path();
+    let parsing_path = parsing_rules_path();
+    let mut player_modified = rules_file_modified(&player_path);
+    let mut parsing_modified = rules_file_modified(&parsing_path);
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let modified = rules_file_modified(&player_path);
+        if modified != player_modified {
+            player_modified = modified;
+            let result = media_player::load_rules_file(&player_path);
+            emit_rules_result(&app, "player_rules.json", result);
+        }
+
+        let modified = rules_file_modified(&parsing_path);
+        if modified != parsing_modified {
+            parsing_modified = modified;
+            let result = parsing_rules::load_rules_file(&parsing_path);
+            emit_rules_result(&app, "parsing_rules.json", result);
+        }
+    }
+}
+
+fn emit_rules_result(app: &tauri::AppHandle, file: &str, result: Result<usize, String>) {
     use serde_json::json;
 
-    // Helper function to search with caching
-    async fn search_with_cache(title: &str) -> Option<anilist::Anime> {
-        // Check cache first
-        if let Some(cached) = get_cached_anime(title) {
-            println!("[Detection] Cache hit for: {}", title);
-            return cached;
+    match result {
+        Ok(count) => {
+            println!("[Rules] Reloaded {} ({} rules)", file, count);
+            let _ = app.emit("rules-reloaded", json!({ "file": file, "count": count }));
+        }
+        Err(e) => {
+            eprintln!("[Rules] Keeping previous {}: {}", file, e);
+            let _ = app.emit("rules-error", json!({ "file": file, "error": e }));
         }
+    }
+}
 
-        // Not in cache, make API call
-        println!("[Detection] Cache miss, searching AniList for: {}", title);
-        let result = match anilist::search_anime(title, 1).await {
-            Ok(results) => results.into_iter().next(),
-            Err(e) => {
-                println!("[Detection] AniList search error: {}", e);
-                None
-            }
-        };
+/// Tauri command to set the mpv IPC socket path (`--input-ipc-server`)
+/// Pass null to restore the default path. The path is saved for the next start.
+#[tauri::command]
+fn set_mpv_ipc_path(path: Option<String>) -> Result<(), String> {
+    mpv_ipc::set_socket_path(path)
+}
+
+/// Tauri command to get the mpv IPC socket path currently in use
+#[tauri::command]
+fn get_mpv_ipc_path() -> String {
+    mpv_ipc::get_socket_path()
+}
+
+/// Tauri command to get VLC/MPC web interface settings
+///
+/// # Arguments
+/// * `player` - "vlc" or "mpc"
+#[tauri::command]
+fn get_web_player_config(player: String) -> Result<web_players::WebInterfaceConfig, String> {
+    web_players::get_config(&player)
+}
+
+/// Tauri command to set and save VLC/MPC web interface settings (enabled, host, port, password)
+#[tauri::command]
+fn set_web_player_config(
+    player: String,
+    config: web_players::WebInterfaceConfig,
+) -> Result<(), String> {
+    web_players::set_config(&player, config)
+}
 
-        // Cache the result (even if None)
-        set_cached_anime(title.to_string(), result.clone());
-        result
+/// Tauri command to get the scrobbler settings
+#[tauri::command]
+fn get_scrobbler_config() -> scrobbler::ScrobbleConfig {
+    scrobbler::get_config()
+}
+
+/// Tauri command to update and save the scrobbler settings
+/// (threshold, minimum watch time and the tokens used for automatic updates)
+#[tauri::command]
+fn set_scrobbler_config(config: scrobbler::ScrobbleConfig) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&config.completion_ratio) {
+        return Err(format!(
+            "completion_ratio must be between 0 and 1, got {}",
+            config.completion_ratio
+        ));
     }
+    scrobbler::set_config(config)
+}
 
-    // 1. Try active window first
-    let active_title = platform_window::get_active_window_title();
-    println!("[Detection] Active window title: {:?}", active_title);
+/// Feed a detection sample to the scrobbler, emitting its events and
+/// pushing completed episodes to AniList/MAL in the background
+fn feed_scrobbler(app: &tauri::AppHandle, observation: Option<scrobbler::Observation>) {
+    for event in scrobbler::observe(observation) {
+        let _ = app.emit(event.event_name(), &event);
+
+        if let scrobbler::ScrobbleEvent::Completed { series, action, .. } = event {
+            println!("[Scrobbler] Completed {:?}: {:?}", series.title, action);
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                for result in scrobbler::sync(&series, &action).await {
+                    println!(
+                        "[Scrobbler] {} sync: {} ({})",
+                        result.service, result.success, result.message
+                    );
+                    if result.reauth_required {
+                        emit_reauth_required(&app, &result.message);
+                    }
+                    let _ = app.emit("scrobble-synced", &result);
+                }
+            });
+        }
+    }
+}
 
-    if let Some(ref window_title) = active_title {
-        let player_result = media_player::detect_media_player(window_title);
-        println!("[Detection] Media player detected: {:?}", player_result);
+/// Search AniList for a parsed title, going through the in-memory cache
+/// The cache holds the search candidates, not the match: `title_matcher`
+/// ranks them for every lookup, since the episode and year take part in the
+/// score. A title nothing scores well enough for stays unmatched
+async fn search_with_cache(parsed: &title_parser::ParsedTitle) -> Option<anilist::Anime> {
+    let title = parsed.title.as_deref()?;
+    let formats = title_matcher::search_formats(parsed.kind);
+    let cache_key = if formats.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} [{}]", title, formats.join(","))
+    };
 
-        if let Some(player) = player_result {
-            let parsed = title_parser::parse_window_title(window_title);
-            println!(
-                "[Detection] Parsed result: title={:?}, episode={:?}",
-                parsed.title, parsed.episode
-            );
+    // Check cache first
+    let candidates = match get_cached_candidates(&cache_key) {
+        Some(candidates) => {
+            println!("[Detection] Cache hit for: {}", cache_key);
+            candidates
+        }
+        None => {
+            println!("[Detection] Cache miss, searching AniList for: {}", cache_key);
+            let candidates = match title_matcher::search_candidates(title, parsed.kind).await {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    // Cached empty too, so a failing search isn't retried every poll
+                    println!("[Detection] AniList search error: {}", e);
+                    Vec::new()
+                }
+            };
+            set_cached_candidates(cache_key, candidates.clone());
+            candidates
+        }
+    };
 
-            let anime_match = if let Some(ref title) = parsed.title {
-                search_with_cache(title).await
-            } else {
-                None
+    title_matcher::best_match(parsed, candidates).map(|m| m.anime)
+}
+
+/// Look a parsed title up in the user's learned aliases
+/// The alias's episode offset is returned as a mapping, like a season's
+async fn match_alias(
+    parsed: &title_parser::ParsedTitle,
+    folder: Option<&str>,
+) -> Option<(anilist::Anime, Option<episode_mapper::MappedEpisode>)> {
+    let title = parsed.title.as_deref()?;
+    let alias = match title_aliases::find_alias(title, parsed.release_group.as_deref(), folder) {
+        Ok(alias) => alias?,
+        Err(e) => {
+            println!("[Detection] Alias lookup error: {}", e);
+            return None;
+        }
+    };
+
+    let anime = match alias.anilist_id {
+        Some(id) => anilist::get_anime_by_id(id).await,
+        None => anilist::get_anime_by_mal_id(alias.mal_id?).await,
+    };
+    let anime = match anime {
+        Ok(anime) => anime,
+        Err(e) => {
+            println!("[Detection] Alias entry fetch error: {}", e);
+            return None;
+        }
+    };
+    println!("[Detection] Alias hit for '{}' → {}", title, anime.id);
+
+    // An episode at or below the offset is left as parsed
+    let mapped = parsed
+        .episode
+        .filter(|_| parsed.kind == title_parser::MediaKind::Episode)
+        .and_then(|episode| alias.map_episode(episode))
+        .map(|episode| episode_mapper::MappedEpisode {
+            media_id: anime.id,
+            episode,
+            offset: alias.episode_offset,
+        });
+    Some((anime, mapped))
+}
+
+/// Match a sample against the user's aliases, else search AniList and move
+/// absolute or season-numbered episodes to their season's entry
+/// ("One Punch Man - 15" → season 2, episode 3)
+async fn match_with_seasons(
+    sample: &detection::MediaSample,
+) -> (
+    Option<anilist::Anime>,
+    Option<episode_mapper::MappedEpisode>,
+) {
+    let parsed = &sample.parsed;
+
+    // The user's corrections come before any search
+    if let Some((anime, mapped)) = match_alias(parsed, sample.folder().as_deref()).await {
+        return (Some(anime), mapped);
+    }
+
+    let anime = search_with_cache(parsed).await;
+    let (anime, episode) = match (anime, parsed.episode) {
+        (Some(anime), Some(episode)) if parsed.kind == title_parser::MediaKind::Episode => {
+            (anime, episode)
+        }
+        (anime, _) => return (anime, None),
+    };
+
+    let mapped = match episode_mapper::map_episode(&anime, parsed.season, episode).await {
+        Ok(Some(mapped)) => mapped,
+        Ok(None) => return (Some(anime), None),
+        Err(e) => {
+            println!("[Detection] Episode mapping error: {}", e);
+            return (Some(anime), None);
+        }
+    };
+    if mapped.media_id == anime.id {
+        return (Some(anime), Some(mapped));
+    }
+
+    match anilist::get_anime_by_id(mapped.media_id).await {
+        Ok(season) => (Some(season), Some(mapped)),
+        Err(e) => {
+            println!("[Detection] Season entry fetch error: {}", e);
+            (Some(anime), None)
+        }
+    }
+}
+
+/// Progress on the logged-in user's AniList list for a matched anime
+/// Lets the scrobbler tell a rewatch from a new episode after a restart
+async fn lookup_list_progress(
+    app: &tauri::AppHandle,
+    anime: Option<&anilist::Anime>,
+) -> Option<i32> {
+    let result = anilist::viewer_list_progress(anime?.id).await;
+    check_reauth(app, &result);
+    match result {
+        Ok(progress) => progress,
+        Err(e) => {
+            println!("[Detection] List progress lookup error: {}", e);
+            None
+        }
+    }
+}
+
+/// Sample the detection sources once, without any AniList lookup
+/// Order: player integrations → active window → every visible window
+async fn sample_media(
+    active_window: Option<&media_player::WindowInfo>,
+) -> Option<detection::MediaSample> {
+    // 1. Ask players directly first - they report the exact file and position.
+    //    Without a video file name we fall through to the window steps.
+    if let Some(playback) = read_playback_sources().await {
+        if let Some(target) = playback
+            .parse_target()
+            .filter(|t| !parsing_rules::is_ignored(t))
+        {
+            // Local files: parent folders fill in what the file name leaves out
+            let parse = match playback.local_path() {
+                Some(path) => title_parser::parse_media_path_ranked(&path),
+                None => title_parser::parse_window_title_ranked(&target),
             };
-            println!("[Detection] AniList match found: {}", anime_match.is_some());
-
-            return Ok(json!({
-                "status": "detected",
-                "player": format!("{:?}", player),
-                "window_title": window_title,
-                "parsed": {
-                    "title": parsed.title,
-                    "episode": parsed.episode,
-                    "season": parsed.season
-                },
-                "anilist_match": anime_match
-            })
-            .to_string());
+            return Some(detection::MediaSample::new(
+                playback.player,
+                None,
+                target,
+                parse,
+                Some(playback),
+            ));
         }
     }
 
-    // 2. If active window isn't a media player, search ALL visible windows
-    let all_titles = platform_window::get_all_visible_window_titles();
-    println!(
-        "[Detection] Fallback: searching {} visible windows",
-        all_titles.len()
-    );
-    for (i, title) in all_titles.iter().enumerate() {
-        println!("[Detection] Window {}: {:?}", i, title);
+    // 2. Try active window
+    if let Some(window) = active_window.filter(|w| !parsing_rules::is_ignored(&w.title)) {
+        if let Some(found) = media_player::match_media_player(window) {
+            return Some(detection::MediaSample::new(
+                found.player,
+                Some(found.rule),
+                window.title.clone(),
+                title_parser::parse_window_title_ranked(&window.title),
+                None,
+            ));
+        }
     }
 
-    for window_title in all_titles {
-        if let Some(player) = media_player::detect_media_player(&window_title) {
-            let parsed = title_parser::parse_window_title(&window_title);
-            println!(
-                "[Detection] Fallback found browser: {:?}, parsed title={:?}, ep={:?}",
-                player, parsed.title, parsed.episode
-            );
+    // 3. If active window isn't a media player, search ALL visible windows
+    for window in platform_window::get_all_visible_windows() {
+        if parsing_rules::is_ignored(&window.title) {
+            continue;
+        }
+        if let Some(found) = media_player::match_media_player(&window) {
+            let parse = title_parser::parse_window_title_ranked(&window.title);
 
             // Only count as "detected" if we actually parsed a title or episode
             // This avoids catching empty media player windows
-            if parsed.title.is_some() || parsed.episode.is_some() {
-                let anime_match = if let Some(ref title) = parsed.title {
-                    search_with_cache(title).await
-                } else {
-                    None
-                };
-
-                return Ok(json!({
-                    "status": "detected",
-                    "player": format!("{:?}", player),
-                    "window_title": window_title,
-                    "parsed": {
-                        "title": parsed.title,
-                        "episode": parsed.episode,
-                        "season": parsed.season
-                    },
-                    "anilist_match": anime_match
-                })
-                .to_string());
+            if parse.best.parsed.title.is_some() || parse.best.parsed.episode.is_some() {
+                return Some(detection::MediaSample::new(
+                    found.player,
+                    Some(found.rule),
+                    window.title,
+                    parse,
+                    None,
+                ));
             }
         }
     }
 
-    // 3. Fallback
-    let status = if active_title.is_some() {
-        "not_media_player"
-    } else {
-        "no_window"
+    None
+}
+
+/// Tauri command to get the anime currently detected by the background loop
+///
+/// Returns the loop's debounced sample and its cached match; it never samples,
+/// searches or feeds the scrobbler itself, so polling it costs nothing.
+///
+/// # Returns
+/// * JSON with detected anime info including parsed title, episode, and matched AniList entry
+#[tauri::command]
+fn detect_anime_command() -> Result<String, String> {
+    use serde_json::json;
+
+    if detection::is_paused() {
+        return Ok(json!({ "status": "paused" }).to_string());
+    }
+
+    let snapshot = detection::snapshot();
+    let Some(sample) = snapshot.media else {
+        let active_window = platform_window::get_active_window_info();
+        let status = if active_window.is_some() {
+            "not_media_player"
+        } else {
+            "no_window"
+        };
+
+        return Ok(json!({
+            "status": status,
+            "window": active_window.map(|w| w.title).unwrap_or_default()
+        })
+        .to_string());
     };
 
     Ok(json!({
-        "status": status,
-        "window": active_title.unwrap_or_default()
+        "status": "detected",
+        "player": format!("{:?}", sample.player),
+        "matched_rule": sample.matched_rule,
+        "window_title": sample.window_title,
+        "parsed": sample.parsed,
+        "confidence": sample.confidence,
+        "needs_confirmation": !sample.is_confident(),
+        "alternatives": sample.alternatives,
+        "anilist_match": snapshot.anilist_match,
+        "episode_mapping": snapshot.episode_mapping,
+        "progress": sample.playback.as_ref().and_then(|p| p.progress_ratio()),
+        "playback": sample.playback
     })
     .to_string())
 }
 
+/// Background detection loop started from `setup`
+///
+/// Samples on the configured interval, emits `media-detected` /
+/// `media-changed` / `media-stopped` on debounced transitions and keeps the
+/// scrobbler fed, so tracking works while the window is hidden in the tray.
+async fn run_detection_loop(app: tauri::AppHandle) {
+    let mut debouncer = detection::Debouncer::new();
+    let mut anime_match: Option<anilist::Anime> = None;
+    let mut episode_mapping: Option<episode_mapper::MappedEpisode> = None;
+    let mut list_progress: Option<i32> = None;
+
+    loop {
+        let config = detection::get_config();
+
+        if detection::is_paused() {
+            if let Some(transition) = debouncer.reset() {
+                anime_match = None;
+                episode_mapping = None;
+                list_progress = None;
+                let _ = app.emit(transition.event_name(), transition.payload(None));
+                feed_scrobbler(&app, None);
+                detection::publish(detection::Snapshot::default());
+            }
+        } else {
+            let active_window = platform_window::get_active_window_info();
+            let sample = sample_media(active_window.as_ref()).await;
+
+            if let Some(transition) = debouncer.update(sample, config.debounce_samples) {
+                // Only look titles up when what's playing actually changes
+                (anime_match, episode_mapping) = match debouncer.current() {
+                    Some(current) if current.is_confident() => match_with_seasons(current).await,
+                    _ => (None, None),
+                };
+                list_progress = lookup_list_progress(&app, anime_match.as_ref()).await;
+                println!(
+                    "[Detection] {} ({:?})",
+                    transition.event_name(),
+                    debouncer.current().map(|s| &s.window_title)
+                );
+                let _ = app.emit(
+                    transition.event_name(),
+                    transition.payload(anime_match.as_ref()),
+                );
+            }
+
+            // Unconfirmed guesses are never scrobbled
+            let observation = debouncer
+                .current()
+                .filter(|s| s.is_confident())
+                .and_then(|s| {
+                    let parsed =
+                        episode_mapping.map_or_else(|| s.parsed.clone(), |m| m.apply(&s.parsed));
+                    scrobbler::Observation::from_detection(
+                        &parsed,
+                        anime_match.as_ref(),
+                        s.playback.as_ref(),
+                    )
+                    .map(|o| o.with_list_progress(list_progress))
+                });
+            feed_scrobbler(&app, observation);
+
+            detection::publish(detection::Snapshot {
+                media: debouncer.current().cloned(),
+                anilist_match: anime_match.clone(),
+                episode_mapping,
+            });
+        }
+
+        tokio::time::sleep(Duration::from_secs(config.interval_secs)).await;
+    }
+}
+
+/// Tray menu item mirroring the detection pause state
+struct DetectionPauseItem(CheckMenuItem<tauri::Wry>);
+
+/// Pause or resume background detection, keeping the tray checkbox in sync
+fn apply_detection_paused(app: &tauri::AppHandle, paused: bool) {
+    detection::set_paused(paused);
+    if let Some(item) = app.try_state::<DetectionPauseItem>() {
+        let _ = item.0.set_checked(paused);
+    }
+    println!("[Detection] Background detection paused: {}", paused);
+    let _ = app.emit("detection-paused", paused);
+}
+
+/// Tauri command to get the background detection settings
+#[tauri::command]
+fn get_detection_config() -> detection::DetectionConfig {
+    detection::get_config()
+}
+
+/// Tauri command to change the sampling interval and debounce count
+#[tauri::command]
+fn set_detection_config(config: detection::DetectionConfig) -> Result<(), String> {
+    detection::set_config(config)
+}
+
+/// Tauri command to pause/resume background detection (same as the tray toggle)
+#[tauri::command]
+fn set_detection_paused(app: tauri::AppHandle, paused: bool) {
+    apply_detection_paused(&app, paused);
+}
+
+/// Tauri command to check whether background detection is paused
+#[tauri::command]
+fn is_detection_paused() -> bool {
+    detection::is_paused()
+}
+
 /// Tauri command to update anime progress on AniList
 ///
 /// # Arguments
-/// * `access_token` - OAuth access token
+/// * `access_token` - OAuth access token (the stored login when omitted)
 /// * `media_id` - AniList media ID
 /// * `progress` - Episode number
 /// * `status` - Optional status (CURRENT, COMPLETED, etc.)
 ///
 /// # Returns
-/// * JSON with updated entry or error
+/// * JSON with updated entry or error; a refused token also emits `anilist-reauth-required`
 #[tauri::command]
 async fn update_anime_progress_command(
-    access_token: String,
+    app: tauri::AppHandle,
+    access_token: Option<String>,
     media_id: i32,
     progress: i32,
     status: Option<String>,
 ) -> Result<String, String> {
+    let access_token = anilist_token(access_token)?;
     let status_ref = status.as_deref();
-    let entry =
-        anilist::update_media_progress(&access_token, media_id, progress, status_ref).await?;
+    let result =
+        anilist::update_media_progress(&access_token, media_id, progress, status_ref).await;
+    check_reauth(&app, &result);
+    let entry = result?;
     serde_json::to_string(&entry).map_err(|e| format!("Serialization error: {}", e))
 }
 
-/// Tauri command to search anime progressively (word by word)
-/// Uses the parsed title and searches AniList starting with 1 word
+/// Tauri command to update manga reading progress on AniList
+///
+/// # Arguments
+/// * `access_token` - OAuth access token (the stored login when omitted)
+/// * `media_id` - AniList manga ID
+/// * `chapters` - Chapters read
+/// * `volumes` - Optional volumes read
+/// * `status` - Optional status (CURRENT, COMPLETED, etc.)
+///
+/// # Returns
+/// * JSON with the saved entry
+#[tauri::command]
+async fn update_manga_progress_command(
+    app: tauri::AppHandle,
+    access_token: Option<String>,
+    media_id: i32,
+    chapters: i32,
+    volumes: Option<i32>,
+    status: Option<String>,
+) -> Result<String, String> {
+    let access_token = anilist_token(access_token)?;
+    let result = anilist::update_manga_progress(
+        &access_token,
+        media_id,
+        chapters,
+        volumes,
+        status.as_deref(),
+    )
+    .await;
+    check_reauth(&app, &result);
+    let entry = result?;
+    serde_json::to_string(&entry).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to get a user's AniList anime or manga list
+/// Private lists need the stored login (see `anilist_auth`)
+///
+/// # Arguments
+/// * `user` - AniList user name or numeric user ID
+/// * `media_type` - ANIME (default) or MANGA
+/// * `status` - Optional status filter (CURRENT, PLANNING, COMPLETED, ...)
+///
+/// # Returns
+/// * JSON array of list entries, most recently updated first
+#[tauri::command]
+async fn get_user_media_list_command(
+    app: tauri::AppHandle,
+    user: String,
+    media_type: Option<String>,
+    status: Option<String>,
+) -> Result<String, String> {
+    let access_token = anilist_auth::access_token();
+    let media_type = media_type.as_deref().unwrap_or("ANIME");
+    let result = anilist::get_user_media_list(
+        &user,
+        media_type,
+        status.as_deref(),
+        access_token.as_deref(),
+    )
+    .await;
+    check_reauth(&app, &result);
+    let entries = result?;
+    serde_json::to_string(&entries).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to create or update an AniList list entry
+/// Covers score, volumes, rewatches, notes, privacy, custom lists and dates
+///
+/// # Arguments
+/// * `access_token` - OAuth access token (the stored login when omitted)
+/// * `update` - Media ID and the fields to change (unset fields stay as they are)
+///
+/// # Returns
+/// * JSON with the saved entry
+#[tauri::command]
+async fn save_media_list_entry_command(
+    app: tauri::AppHandle,
+    access_token: Option<String>,
+    update: anilist::MediaListUpdate,
+) -> Result<String, String> {
+    let access_token = anilist_token(access_token)?;
+    let result = anilist::save_media_list_entry(&access_token, &update).await;
+    check_reauth(&app, &result);
+    let entry = result?;
+    serde_json::to_string(&entry).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Tauri command to remove an entry from the AniList list
+///
+/// # Arguments
+/// * `access_token` - OAuth access token (the stored login when omitted)
+/// * `entry_id` - List entry ID (not the media ID)
+///
+/// # Returns
+/// * Whether the entry was deleted
+#[tauri::command]
+async fn delete_media_list_entry_command(
+    app: tauri::AppHandle,
+    access_token: Option<String>,
+    entry_id: i32,
+) -> Result<bool, String> {
+    let access_token = anilist_token(access_token)?;
+    let result = anilist::delete_media_list_entry(&access_token, entry_id).await;
+    check_reauth(&app, &result);
+    result.map_err(String::from)
+}
+
+/// Tauri command to match a title against AniList
+/// Candidates are scored by `title_matcher`, like detection does
 ///
 /// # Arguments
 /// * `title` - The parsed anime title to search
 ///
 /// # Returns
-/// * JSON with the matched anime title and search info
+/// * The best scoring anime and its score, or None if nothing matched
 #[tauri::command]
-async fn progressive_search_command(title: String) -> Result<String, String> {
-    let result = anilist::progressive_search_anime(&title).await?;
-    serde_json::to_string(&result).map_err(|e| format!("Serialization error: {}", e))
+async fn match_title_command(title: String) -> Result<Option<title_matcher::TitleMatch>, String> {
+    let parsed = title_parser::ParsedTitle {
+        title: Some(title.trim().to_string()).filter(|t| !t.is_empty()),
+        ..Default::default()
+    };
+    title_matcher::match_parsed_title(&parsed).await
 }
 
 /// Tauri command to download a chapter as CBZ
@@ -437,7 +1214,7 @@ async fn download_image_for_notification(url: String) -> Result<String, String>
 
     // Generate filename from URL hash
     let hash = format!("{:x}", md5_hash(&url));
-    let extension = url.split('.').last().unwrap_or("jpg");
+    let extension = url.split('.').next_back().unwrap_or("jpg");
     let filename = format!("{}.{}", hash, extension);
     let file_path: PathBuf = cache_dir.join(&filename);
 
@@ -874,7 +1651,7 @@ async fn proxy_request(
                 match res.text().await {
                     Ok(text) => {
                         let response_data = serde_json::json!({
-                            "ok": status >= 200 && status < 300,
+                            "ok": (200..300).contains(&status),
                             "status": status,
                             "headers": res_headers,
                             "data": text
@@ -946,13 +1723,44 @@ pub fn run() {
             get_active_media_window,
             search_anime_command,
             get_anime_by_id_command,
+            search_manga_command,
+            get_manga_by_id_command,
             match_anime_from_window_command,
             file_system::get_folder_contents,
             exchange_login_code,
+            set_anilist_token,
+            clear_anilist_token,
+            get_anilist_token_status,
             parse_window_title_command,
+            parse_media_path_command,
+            parse_manga_path_command,
             detect_anime_command,
+            set_mpv_ipc_path,
+            get_mpv_ipc_path,
+            get_web_player_config,
+            set_web_player_config,
+            reload_player_rules,
+            get_player_rules,
+            reload_parsing_rules,
+            check_parsing_rules,
+            add_title_alias,
+            list_title_aliases,
+            remove_title_alias,
+            export_title_aliases,
+            import_title_aliases,
+            get_parsing_rules,
+            get_scrobbler_config,
+            set_scrobbler_config,
+            get_detection_config,
+            set_detection_config,
+            set_detection_paused,
+            is_detection_paused,
             update_anime_progress_command,
-            progressive_search_command,
+            update_manga_progress_command,
+            get_user_media_list_command,
+            save_media_list_entry_command,
+            delete_media_list_entry_command,
+            match_title_command,
             download_image_for_notification,
             download_image_for_notification,
             cbz_reader::get_cbz_info,
@@ -988,6 +1796,39 @@ pub fn run() {
                 stream_server::start_server().await;
             });
 
+            // Load user-defined media player rules on top of the built-ins
+            match media_player::load_rules_file(&player_rules_path()) {
+                Ok(count) => println!("[Startup] Loaded {} media player rules", count),
+                Err(e) => eprintln!("[Startup] Ignoring player rules: {}", e),
+            }
+            match parsing_rules::load_rules_file(&parsing_rules_path()) {
+                Ok(count) => println!("[Startup] Loaded {} title parsing rules", count),
+                Err(e) => eprintln!("[Startup] Ignoring parsing rules: {}", e),
+            }
+
+            // Pick up edits to either rules file without a restart
+            let rules_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_rules_watcher(rules_handle).await;
+            });
+
+            // Watch for mpv's IPC socket in the background
+            tauri::async_runtime::spawn(async {
+                mpv_ipc::run_watcher().await;
+            });
+
+            // Warn before the stored AniList login expires
+            let token_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_token_watch(token_handle).await;
+            });
+
+            // Sample media players in the background and push media-* events
+            let detection_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_detection_loop(detection_handle).await;
+            });
+
             // Register deep links at runtime for development mode (Windows/Linux)
             // This is needed because deep links are only registered on install by default
             #[cfg(any(target_os = "linux", windows))]
@@ -999,10 +1840,19 @@ pub fn run() {
             // === System Tray Setup ===
             // Create tray menu items
             let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+            let pause_item = CheckMenuItem::with_id(
+                app,
+                "pause_detection",
+                "Pause Detection",
+                true,
+                detection::is_paused(),
+                None::<&str>,
+            )?;
             let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+            app.manage(DetectionPauseItem(pause_item.clone()));
 
             // Create the tray menu
-            let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
+            let menu = Menu::with_items(app, &[&show_item, &pause_item, &quit_item])?;
 
             // Build the tray icon
             let _tray = TrayIconBuilder::new()
@@ -1018,6 +1868,9 @@ pub fn run() {
                             let _ = window.emit("window-visibility", "visible");
                         }
                     }
+                    "pause_detection" => {
+                        apply_detection_paused(app, !detection::is_paused());
+                    }
                     "quit" => {
                         app.exit(0);
                     }
@@ -0,0 +1,223 @@
+//! Linux Window Detection Module
+//!
+//! PURPOSE: Detect window titles on Linux using the X11 EWMH hints
+//! (`_NET_ACTIVE_WINDOW`, `_NET_CLIENT_LIST`, `_NET_WM_NAME`)
+//! Provides the same interface as win_name.rs for cross-platform compatibility
+//!
+//! NOTE: Wayland does not let clients inspect other applications' windows.
+//! On a Wayland session only XWayland windows are visible through `$DISPLAY`,
+//! and without any X server both functions simply report nothing.
+#![cfg(target_os = "linux")]
+
+use crate::media_player::WindowInfo;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, MapState, Window};
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+    /// EWMH atoms used for window lookups
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        UTF8_STRING,
+    }
+}
+
+/// An open X11 connection together with the root window and interned atoms
+struct X11Session {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+/// Connect to the X server named by `$DISPLAY`
+///
+/// Returns None on pure Wayland sessions or when no X server is reachable,
+/// so callers degrade to "no window" instead of erroring.
+fn connect() -> Option<X11Session> {
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen_num)?.root;
+    let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+
+    Some(X11Session { conn, root, atoms })
+}
+
+/// Read a 32-bit WINDOW-typed property (e.g. `_NET_ACTIVE_WINDOW`) as a list of window ids
+fn get_window_list_property(session: &X11Session, property: u32) -> Vec<Window> {
+    let reply = match session
+        .conn
+        .get_property(false, session.root, property, AtomEnum::WINDOW, 0, u32::MAX)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+    {
+        Some(reply) => reply,
+        None => return Vec::new(),
+    };
+
+    reply
+        .value32()
+        .map(|values| values.filter(|&w| w != 0).collect())
+        .unwrap_or_default()
+}
+
+/// Get a window's title, preferring the UTF-8 `_NET_WM_NAME` over legacy `WM_NAME`
+fn get_window_title(session: &X11Session, window: Window) -> Option<String> {
+    let net_wm_name = session
+        .conn
+        .get_property(
+            false,
+            window,
+            session.atoms._NET_WM_NAME,
+            session.atoms.UTF8_STRING,
+            0,
+            u32::MAX,
+        )
+        .ok()?
+        .reply()
+        .ok()?;
+
+    if !net_wm_name.value.is_empty() {
+        return String::from_utf8(net_wm_name.value).ok();
+    }
+
+    // Fallback for older toolkits that only set the ICCCM property (Latin-1)
+    let wm_name = session
+        .conn
+        .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::STRING, 0, u32::MAX)
+        .ok()?
+        .reply()
+        .ok()?;
+
+    if wm_name.value.is_empty() {
+        return None;
+    }
+
+    Some(wm_name.value.iter().map(|&b| b as char).collect())
+}
+
+/// Get the window class from `WM_CLASS` ("instance\0Class\0"), returning the class part
+fn get_window_class(session: &X11Session, window: Window) -> Option<String> {
+    let reply = session
+        .conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, u32::MAX)
+        .ok()?
+        .reply()
+        .ok()?;
+
+    let mut parts = reply
+        .value
+        .split(|&b| b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).into_owned());
+    let instance = parts.next();
+    parts.next().or(instance)
+}
+
+/// Get the process name via `_NET_WM_PID` and `/proc/<pid>/comm`
+///
+/// Only works for local clients; windows forwarded from other hosts return None.
+fn get_process_name(session: &X11Session, window: Window) -> Option<String> {
+    let reply = session
+        .conn
+        .get_property(
+            false,
+            window,
+            session.atoms._NET_WM_PID,
+            AtomEnum::CARDINAL,
+            0,
+            1,
+        )
+        .ok()?
+        .reply()
+        .ok()?;
+    let pid = reply.value32()?.next()?;
+
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+fn get_window_info(session: &X11Session, window: Window) -> Option<WindowInfo> {
+    let title = get_window_title(session, window).filter(|title| !title.is_empty())?;
+
+    Some(WindowInfo {
+        title,
+        process_name: get_process_name(session, window),
+        class_name: get_window_class(session, window),
+    })
+}
+
+/// Check whether a window is currently mapped (minimized windows are unmapped)
+fn is_window_viewable(session: &X11Session, window: Window) -> bool {
+    session
+        .conn
+        .get_window_attributes(window)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|attrs| attrs.map_state == MapState::VIEWABLE)
+        .unwrap_or(false)
+}
+
+/// Get the title of the currently focused window on Linux
+///
+/// Reads `_NET_ACTIVE_WINDOW` from the root window, which every
+/// EWMH-compliant window manager (GNOME, KDE, Xfce, i3, ...) maintains.
+///
+/// # Returns
+/// * `Some(String)` - The window title if successfully retrieved
+/// * `None` - If no window is active, the WM isn't EWMH-compliant, or no X server is available
+pub fn get_active_window_title() -> Option<String> {
+    let session = connect()?;
+    let active = *get_window_list_property(&session, session.atoms._NET_ACTIVE_WINDOW).first()?;
+
+    get_window_title(&session, active).filter(|title| !title.is_empty())
+}
+
+/// Title, process and WM_CLASS of the focused window
+pub fn get_active_window_info() -> Option<WindowInfo> {
+    let session = connect()?;
+    let active = *get_window_list_property(&session, session.atoms._NET_ACTIVE_WINDOW).first()?;
+
+    get_window_info(&session, active)
+}
+
+/// Get all visible top-level windows
+///
+/// Enumerates `_NET_CLIENT_LIST` (the window manager's list of managed
+/// client windows) and skips windows that are minimized or untitled.
+///
+/// # Returns
+/// * `Vec<WindowInfo>` - Title, process and WM_CLASS of each window,
+///   empty if no X server is available
+pub fn get_all_visible_windows() -> Vec<WindowInfo> {
+    let session = match connect() {
+        Some(s) => s,
+        None => return Vec::new(),
+    };
+
+    get_window_list_property(&session, session.atoms._NET_CLIENT_LIST)
+        .into_iter()
+        .filter(|&window| is_window_viewable(&session, window))
+        .filter_map(|window| get_window_info(&session, window))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_active_window_title() {
+        // Returns None in headless/Wayland-only environments
+        let title = get_active_window_title();
+        println!("Active window title: {:?}", title);
+    }
+
+    #[test]
+    fn test_get_all_visible_windows() {
+        let windows = get_all_visible_windows();
+        println!("Visible windows: {:?}", windows);
+        // We can't assert specific values since it depends on running apps
+    }
+}
@@ -6,6 +6,7 @@
 //! NOTE: Requires Screen Recording permission to see other app windows
 #![cfg(target_os = "macos")]
 
+use crate::media_player::WindowInfo;
 use core_foundation::base::TCFType;
 use core_foundation::dictionary::CFDictionaryRef;
 use core_foundation::number::CFNumber;
@@ -24,6 +25,14 @@ use core_graphics::window::{
 /// * `Some(String)` - The window title if successfully retrieved
 /// * `None` - If no window is active or an error occurred
 pub fn get_active_window_title() -> Option<String> {
+    get_active_window_info().map(|w| w.title)
+}
+
+/// Get title and owning application of the frontmost window
+///
+/// macOS has no window classes, so `class_name` is always None and
+/// `process_name` holds the owner application name (e.g. "VLC", "IINA").
+pub fn get_active_window_info() -> Option<WindowInfo> {
     let options = kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements;
 
     let window_list = unsafe { CGWindowListCopyWindowInfo(options, kCGNullWindowID) };
@@ -54,11 +63,16 @@ pub fn get_active_window_title() -> Option<String> {
                 // Check if this window belongs to a normal app (layer 0)
                 if let Some(layer) = get_window_layer(*dict) {
                     if layer == 0 {
+                        let owner = get_window_owner_name(*dict);
                         // Clean up
                         unsafe {
                             core_foundation::base::CFRelease(window_list as _);
                         }
-                        return Some(title);
+                        return Some(WindowInfo {
+                            title,
+                            process_name: owner,
+                            class_name: None,
+                        });
                     }
                 }
             }
@@ -69,11 +83,16 @@ pub fn get_active_window_title() -> Option<String> {
     for dict in windows.iter() {
         if let Some(title) = get_window_name(*dict) {
             if !title.is_empty() && title != "Notification Center" && title != "Control Center" {
+                let owner = get_window_owner_name(*dict);
                 // Clean up
                 unsafe {
                     core_foundation::base::CFRelease(window_list as _);
                 }
-                return Some(title);
+                return Some(WindowInfo {
+                    title,
+                    process_name: owner,
+                    class_name: None,
+                });
             }
         }
     }
@@ -86,15 +105,16 @@ pub fn get_active_window_title() -> Option<String> {
     None
 }
 
-/// Get titles of all visible windows from common media players and browsers
+/// Get all visible windows from common media players and browsers
 ///
 /// Uses Core Graphics API to enumerate all visible windows and filter
 /// by owner application name.
 ///
 /// # Returns
-/// * `Vec<String>` - List of window titles from media player/browser applications
-pub fn get_all_visible_window_titles() -> Vec<String> {
-    let mut titles = Vec::new();
+/// * `Vec<WindowInfo>` - Windows from media player/browser applications,
+///   with the owner application name as `process_name`
+pub fn get_all_visible_windows() -> Vec<WindowInfo> {
+    let mut visible = Vec::new();
 
     // Apps we're interested in
     let target_apps: Vec<&str> = vec![
@@ -124,7 +144,7 @@ pub fn get_all_visible_window_titles() -> Vec<String> {
     let window_list = unsafe { CGWindowListCopyWindowInfo(options, kCGNullWindowID) };
 
     if window_list.is_null() {
-        return titles;
+        return visible;
     }
 
     let windows: Vec<CFDictionaryRef> = unsafe {
@@ -151,7 +171,11 @@ pub fn get_all_visible_window_titles() -> Vec<String> {
             if is_target {
                 if let Some(title) = get_window_name(*dict) {
                     if !title.is_empty() {
-                        titles.push(title);
+                        visible.push(WindowInfo {
+                            title,
+                            process_name: Some(owner.clone()),
+                            class_name: None,
+                        });
                     }
                 }
             }
@@ -163,7 +187,7 @@ pub fn get_all_visible_window_titles() -> Vec<String> {
         core_foundation::base::CFRelease(window_list as _);
     }
 
-    titles
+    visible
 }
 
 /// Extract window name from a Core Foundation dictionary
@@ -242,9 +266,9 @@ mod tests {
     }
 
     #[test]
-    fn test_get_all_visible_window_titles() {
-        let titles = get_all_visible_window_titles();
-        println!("Visible window titles: {:?}", titles);
+    fn test_get_all_visible_windows() {
+        let windows = get_all_visible_windows();
+        println!("Visible windows: {:?}", windows);
         // We can't assert specific values since it depends on running apps
     }
 }
@@ -0,0 +1,431 @@
+//! Manga Parser Module
+//!
+//! PURPOSE: Read series, volume, chapter, part and scanlation group from
+//! manga archive names (CBZ/CBR files opened by `cbz_reader` or written by
+//! `downloader`), for manga progress tracking and library grouping
+//!
+//! APPROACH: Tokenize the name with `title_tokenizer`, then look for volume
+//! (`Vol.03`, `v05`), chapter (`Ch.021.5`, `c045`, `Chapter 12`) and part
+//! markers. The words before the first marker are the series. Without
+//! markers, a trailing number (`Title 045.cbz`) is read as the chapter.
+//! `downloader` names files `Chapter N.cbz` inside a series folder, so
+//! `parse_manga_path` falls back to the folder names for the series.
+
+use crate::title_tokenizer::{self, Token, TokenKind};
+use serde::{Deserialize, Serialize};
+
+/// Known manga archive extensions (lowercase, with dot)
+const MANGA_EXTENSIONS: [&str; 7] = [".cbz", ".cbr", ".cb7", ".zip", ".rar", ".pdf", ".epub"];
+
+/// Bracketed tags that are neither a group nor part of the name
+const MANGA_TAGS: [&str; 8] = [
+    "digital", "f", "c2c", "complete", "raw", "color", "colored", "hq",
+];
+
+/// Result of parsing a manga file name
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ParsedManga {
+    /// Series name (if detected)
+    pub series: Option<String>,
+    /// Volume number from `Vol.03`, `v05` or `Volume 3`
+    pub volume: Option<i32>,
+    /// Chapter number; side chapters keep their fraction (`Ch.021.5` → 21.5)
+    pub chapter: Option<f64>,
+    /// Part of a chapter split across files (`Part 2`, `Pt.2`)
+    pub part: Option<i32>,
+    /// Chapter name from `Chapter 12 - Subtitle`
+    pub chapter_title: Option<String>,
+    /// Scanlation or release group, e.g. "Group" from `[Group]`
+    pub group: Option<String>,
+    /// Oneshot (a single-chapter story); `chapter` stays empty
+    pub is_oneshot: bool,
+    /// File extension without the dot, lowercase
+    pub file_extension: Option<String>,
+}
+
+/// What a marker word numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Volume,
+    Chapter,
+    Part,
+}
+
+/// Marker spellings, longest first; single letters only count with the
+/// number attached (`c045`, `v05`)
+const MARKERS: [(&str, Marker); 9] = [
+    ("volume", Marker::Volume),
+    ("vol", Marker::Volume),
+    ("v", Marker::Volume),
+    ("chapter", Marker::Chapter),
+    ("chap", Marker::Chapter),
+    ("ch", Marker::Chapter),
+    ("c", Marker::Chapter),
+    ("part", Marker::Part),
+    ("pt", Marker::Part),
+];
+
+/// Parse a manga archive name
+///
+/// # Supported Formats
+/// - `Title Vol.03 Ch.021.5.cbz`, `Title v01 c005.cbz` (volume + chapter)
+/// - `Title c045.cbz`, `Title - 045.cbz`, `Title 045.cbz` (chapter only)
+/// - `[Group] Title v05 (Digital).cbz` (volume only)
+/// - `Chapter 12 - Subtitle.cbz`, `Title Ch. 12 Part 2.cbz`
+/// - `Title (Oneshot).cbz`, `Title - Oneshot.cbz`
+pub fn parse_manga_file_name(file_name: &str) -> ParsedManga {
+    let (body, file_extension) =
+        title_tokenizer::split_extension(file_name.trim(), &MANGA_EXTENSIONS);
+    let tokens = title_tokenizer::tokenize(body);
+
+    let mut parsed = ParsedManga {
+        file_extension,
+        ..Default::default()
+    };
+
+    let mut series_end = None;
+    let mut chapter_at = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        match token.kind {
+            TokenKind::Enclosed => {
+                if is_oneshot(token.text) {
+                    parsed.is_oneshot = true;
+                }
+            }
+            TokenKind::Word if is_oneshot(token.text) => {
+                parsed.is_oneshot = true;
+                series_end.get_or_insert(i);
+            }
+            TokenKind::Word => {
+                if let Some(last) = read_markers(&tokens, i, &mut parsed) {
+                    series_end.get_or_insert(i);
+                    if parsed.chapter.is_some() && chapter_at.is_none() {
+                        chapter_at = Some(last);
+                    }
+                    i = last;
+                }
+            }
+            TokenKind::Delimiter => {}
+        }
+        i += 1;
+    }
+
+    // "Title 045", "Title - 045 - Subtitle"
+    if parsed.chapter.is_none() && parsed.volume.is_none() && !parsed.is_oneshot {
+        if let Some(at) = trailing_chapter(&tokens) {
+            parsed.chapter = take_chapter(tokens[at].text)
+                .filter(|(_, rest)| rest.is_empty())
+                .and_then(|(number, _)| number.parse().ok());
+            series_end = Some(at);
+            chapter_at = Some(at);
+        }
+    }
+
+    if let Some(at) = chapter_at {
+        parsed.chapter_title = chapter_title(&tokens, at + 1);
+    }
+    let series_end = series_end.unwrap_or(tokens.len());
+    parsed.series = Some(title_tokenizer::join_words(&tokens[..series_end]))
+        .filter(|series| !series.is_empty());
+    parsed.group = find_group(&tokens, series_end);
+
+    // A oneshot has no chapter to track
+    if parsed.is_oneshot {
+        parsed.chapter = None;
+    }
+    parsed
+}
+
+/// Parse a manga archive path, taking the series (and volume) from the
+/// folders when the file name doesn't say
+///
+/// # Supported Layouts
+/// - `/Manga/Frieren/Chapter 12.cbz` (downloader layout)
+/// - `/Manga/Frieren/Vol. 03/Chapter 021.cbz` (volume folders)
+/// - `/Manga/[Group] Frieren v05 (Digital).cbz` (file name wins)
+pub fn parse_manga_path(path: &str) -> ParsedManga {
+    let parts: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    let (file_name, folders) = match parts.split_last() {
+        Some(split) => split,
+        None => return ParsedManga::default(),
+    };
+
+    let mut parsed = parse_manga_file_name(file_name);
+    let mut folders = folders.iter().rev();
+
+    // "Vol. 03" folders sit between the series folder and the chapters
+    let mut series_folder = folders.next();
+    let volume_folder = series_folder
+        .map(|f| parse_manga_file_name(f))
+        .filter(|f| f.series.is_none() && f.volume.is_some());
+    if let Some(folder) = volume_folder {
+        parsed.volume = parsed.volume.or(folder.volume);
+        series_folder = folders.next();
+    }
+
+    // The whole folder name is the series ("Mob Psycho 100" has no chapter)
+    if let Some(folder) = series_folder {
+        let tokens = title_tokenizer::tokenize(folder);
+        if parsed.series.is_none() {
+            parsed.series = Some(title_tokenizer::join_words(&tokens)).filter(|s| !s.is_empty());
+        }
+        parsed.group = parsed.group.or_else(|| find_group(&tokens, 0));
+    }
+    parsed
+}
+
+/// Read the markers starting at word `i` ("Vol", "03", "Ch", "021.5" or
+/// "v01c005") into `parsed`
+///
+/// Returns the index of the last token used, or None if word `i` isn't a marker.
+fn read_markers(tokens: &[Token], i: usize, parsed: &mut ParsedManga) -> Option<usize> {
+    let mut last = i;
+    let mut text = tokens[i].text;
+    let mut found = false;
+
+    while !text.is_empty() {
+        let (marker, rest) = match strip_marker(text) {
+            Some(found) => found,
+            None => break,
+        };
+
+        // "Vol. 03": the number is the next word
+        let (number, rest) = if rest.is_empty() && text.len() > 1 {
+            match title_tokenizer::next_word(tokens, last) {
+                Some((j, word)) => match take_chapter(word) {
+                    Some((number, "")) => {
+                        last = j;
+                        (number, "")
+                    }
+                    _ => break,
+                },
+                None => break,
+            }
+        } else {
+            match take_chapter(rest) {
+                Some(found) => found,
+                None => break,
+            }
+        };
+
+        match marker {
+            Marker::Chapter => parsed.chapter = parsed.chapter.or(number.parse().ok()),
+            Marker::Volume => parsed.volume = parsed.volume.or(number.parse().ok()),
+            Marker::Part => parsed.part = parsed.part.or(number.parse().ok()),
+        }
+        found = true;
+        text = rest;
+    }
+
+    if found {
+        Some(last)
+    } else {
+        None
+    }
+}
+
+/// Split a marker off the start of a word: ("ch", "021")
+fn strip_marker(text: &str) -> Option<(Marker, &str)> {
+    MARKERS.iter().find_map(|&(prefix, marker)| {
+        let head = text.get(..prefix.len())?;
+        if !head.eq_ignore_ascii_case(prefix) {
+            return None;
+        }
+        let rest = &text[prefix.len()..];
+        let spelled = prefix.len() > 1 && rest.is_empty();
+        if spelled || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            Some((marker, rest))
+        } else {
+            None
+        }
+    })
+}
+
+/// `021`, `21.5` or `1043` at the start of `s`: the number and what follows it
+fn take_chapter(s: &str) -> Option<(&str, &str)> {
+    let whole = s.bytes().take_while(u8::is_ascii_digit).count();
+    if !(1..=4).contains(&whole) {
+        return None;
+    }
+    let fraction = s[whole..]
+        .strip_prefix('.')
+        .map_or(0, |r| r.bytes().take_while(u8::is_ascii_digit).count());
+    let len = if (1..=2).contains(&fraction) {
+        whole + 1 + fraction
+    } else {
+        whole
+    };
+    Some(s.split_at(len))
+}
+
+/// A chapter number after the series words, followed only by brackets or a
+/// " - Subtitle": `Title 045 (2019)`, `Title - 045 - Subtitle`
+fn trailing_chapter(tokens: &[Token]) -> Option<usize> {
+    (1..tokens.len()).find(|&i| {
+        let is_number = tokens[i].kind == TokenKind::Word
+            && take_chapter(tokens[i].text).is_some_and(|(_, rest)| rest.is_empty());
+        let after_series = tokens[i - 1].kind == TokenKind::Delimiter
+            && tokens[..i].iter().any(|t| t.kind == TokenKind::Word);
+        is_number && after_series && ends_chapter(tokens, i + 1)
+    })
+}
+
+/// Whether the tokens from `i` on can follow a chapter number
+fn ends_chapter(tokens: &[Token], i: usize) -> bool {
+    match tokens.get(i) {
+        None => true,
+        Some(token) if token.kind == TokenKind::Enclosed => true,
+        Some(token) if token.kind == TokenKind::Delimiter => match tokens.get(i + 1) {
+            None => true,
+            Some(next) if next.kind == TokenKind::Enclosed => true,
+            Some(_) => is_spaced_dash(token),
+        },
+        Some(_) => false,
+    }
+}
+
+/// `Chapter 12 - Subtitle (Digital)` → "Subtitle"; only a dash introduces a
+/// title ("Ch. 12 Part 2" has none)
+fn chapter_title(tokens: &[Token], i: usize) -> Option<String> {
+    tokens.get(i).filter(|t| is_spaced_dash(t))?;
+    let end = tokens[i..]
+        .iter()
+        .position(|t| t.kind == TokenKind::Enclosed)
+        .map_or(tokens.len(), |p| i + p);
+    Some(title_tokenizer::join_words(&tokens[i + 1..end])).filter(|t| !t.is_empty())
+}
+
+/// A ` - ` delimiter (dashes inside words like "Kaguya-sama" aren't delimiters)
+fn is_spaced_dash(token: &Token) -> bool {
+    token.kind == TokenKind::Delimiter
+        && token.text.contains('-')
+        && token.text.trim() != token.text
+}
+
+/// The group: a leading `[Group]`, else the last `(Group)` after the series
+/// that isn't a tag (`Title v05 (2019) (Digital) (Group)`)
+fn find_group(tokens: &[Token], series_end: usize) -> Option<String> {
+    let is_group = |t: &&Token| {
+        let text = t.text.trim();
+        t.kind == TokenKind::Enclosed
+            && !text.is_empty()
+            && !is_manga_tag(text)
+            && !is_oneshot(text)
+            // "(2019)"
+            && (text.len() != 4 || !text.bytes().all(|b| b.is_ascii_digit()))
+    };
+
+    let leading = tokens
+        .first()
+        .filter(|t| t.bracket == Some('['))
+        .filter(is_group);
+    leading
+        .or_else(|| tokens.iter().skip(series_end).rev().find(is_group))
+        .map(|t| t.text.trim().to_string())
+}
+
+fn is_manga_tag(text: &str) -> bool {
+    MANGA_TAGS.iter().any(|tag| text.eq_ignore_ascii_case(tag))
+}
+
+/// `Oneshot`, `One-shot`, `One Shot`
+fn is_oneshot(text: &str) -> bool {
+    let letters: String = text
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    letters == "oneshot"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volume_and_chapter() {
+        let result = parse_manga_file_name("Sousou no Frieren Vol.03 Ch.021.5.cbz");
+        assert_eq!(result.series, Some("Sousou no Frieren".to_string()));
+        assert_eq!(result.volume, Some(3));
+        assert_eq!(result.chapter, Some(21.5));
+        assert_eq!(result.file_extension, Some("cbz".to_string()));
+
+        let result = parse_manga_file_name("Chainsaw Man v01 c005.cbz");
+        assert_eq!(result.series, Some("Chainsaw Man".to_string()));
+        assert_eq!(result.volume, Some(1));
+        assert_eq!(result.chapter, Some(5.0));
+
+        let result = parse_manga_file_name("Vol.03 Ch.021.5");
+        assert_eq!(result.series, None);
+        assert_eq!(result.volume, Some(3));
+        assert_eq!(result.chapter, Some(21.5));
+    }
+
+    #[test]
+    fn test_chapter_only() {
+        let result = parse_manga_file_name("One Piece c1043.cbz");
+        assert_eq!(result.series, Some("One Piece".to_string()));
+        assert_eq!(result.chapter, Some(1043.0));
+        assert_eq!(result.volume, None);
+
+        let result = parse_manga_file_name("Kaguya-sama - 045 (2019).cbr");
+        assert_eq!(result.series, Some("Kaguya-sama".to_string()));
+        assert_eq!(result.chapter, Some(45.0));
+
+        let result = parse_manga_file_name("Chapter 12 - The Hero's Funeral.cbz");
+        assert_eq!(result.series, None);
+        assert_eq!(result.chapter, Some(12.0));
+        assert_eq!(result.chapter_title, Some("The Hero's Funeral".to_string()));
+
+        let result = parse_manga_file_name("Dandadan Ch. 12 Part 2.cbz");
+        assert_eq!(result.chapter, Some(12.0));
+        assert_eq!(result.part, Some(2));
+        assert_eq!(result.chapter_title, None);
+    }
+
+    #[test]
+    fn test_group_and_volume_release() {
+        let result = parse_manga_file_name("[Group] Vinland Saga v05 (Digital).cbz");
+        assert_eq!(result.group, Some("Group".to_string()));
+        assert_eq!(result.series, Some("Vinland Saga".to_string()));
+        assert_eq!(result.volume, Some(5));
+        assert_eq!(result.chapter, None);
+
+        let result = parse_manga_file_name("Blue Period v03 (2020) (Digital) (1r0n).cbz");
+        assert_eq!(result.group, Some("1r0n".to_string()));
+        assert_eq!(result.volume, Some(3));
+    }
+
+    #[test]
+    fn test_oneshot() {
+        let result = parse_manga_file_name("Look Back (Oneshot).cbz");
+        assert_eq!(result.series, Some("Look Back".to_string()));
+        assert!(result.is_oneshot);
+        assert_eq!(result.chapter, None);
+
+        let result = parse_manga_file_name("Goodbye, Eri - One-shot.cbz");
+        assert_eq!(result.series, Some("Goodbye, Eri".to_string()));
+        assert!(result.is_oneshot);
+    }
+
+    #[test]
+    fn test_manga_path() {
+        let result = parse_manga_path("/Manga/Frieren/Chapter 12.cbz");
+        assert_eq!(result.series, Some("Frieren".to_string()));
+        assert_eq!(result.chapter, Some(12.0));
+
+        let result = parse_manga_path("D:\\Manga\\Frieren\\Vol. 03\\Chapter 021.cbz");
+        assert_eq!(result.series, Some("Frieren".to_string()));
+        assert_eq!(result.volume, Some(3));
+        assert_eq!(result.chapter, Some(21.0));
+
+        let result = parse_manga_path("/Manga/Mob Psycho 100/c045.cbz");
+        assert_eq!(result.series, Some("Mob Psycho 100".to_string()));
+        assert_eq!(result.chapter, Some(45.0));
+    }
+}
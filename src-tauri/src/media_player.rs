@@ -3,15 +3,22 @@
 /// PURPOSE: Filter out non-media windows using allow-listing
 /// Only track windows from known media players
 ///
-/// APPROACH: Simple, deterministic matching
+/// APPROACH: Rule table, deterministic matching
 /// - No guessing or complex heuristics
-/// - Media players advertise themselves in window titles
-/// - Easy to extend and debug
+/// - Each rule matches on title suffixes/keywords (word boundaries),
+///   title regexes, process name or window class
+/// - The highest-priority matching rule wins and is reported back
+/// - Extra rules load from `player_rules.json` without a rebuild
 ///
 /// NOTE: For more robust detection, consider using Windows Media Session API (SMTC)
 /// in a future enhancement - this current approach relies on window titles.
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)] // The players' own names, also used in serialized output
 pub enum MediaPlayer {
     VLC,
     MPV,
@@ -24,82 +31,475 @@ pub enum MediaPlayer {
     Generic, // For players detected by file extension
 }
 
-/// Detect media player type from window title
-///
-/// Returns Some(MediaPlayer) if the window belongs to a known media player
-/// Returns None for all other windows (VS Code, File Explorer, etc.)
+/// Playback state as reported by a player integration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// Now-playing information read directly from a player (MPRIS, IPC, web interface)
 ///
-/// This is the core filtering logic - only known media players pass through
-pub fn detect_media_player(title: &str) -> Option<MediaPlayer> {
-    let title_lower = title.to_lowercase();
+/// Unlike window titles, these sources know the exact file and how far into it
+/// the user is, so the tracker can rely on real playback state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackInfo {
+    /// Which integration produced this info (e.g. "mpris")
+    pub source: String,
+    /// Player type, mapped from the integration's player name
+    pub player: MediaPlayer,
+    /// File path or URL of the current media (if known)
+    pub url: Option<String>,
+    /// Media title reported by the player (if known)
+    pub title: Option<String>,
+    /// Playback position in seconds
+    pub position_secs: Option<f64>,
+    /// Total duration in seconds
+    pub duration_secs: Option<f64>,
+    pub state: PlaybackState,
+}
 
-    // VLC Media Player
-    if title_lower.contains("vlc media player") || title_lower.contains("vlc") {
-        return Some(MediaPlayer::VLC);
+impl PlaybackInfo {
+    /// The string to hand to `title_parser::parse_window_title`
+    ///
+    /// Only the file name of a video file qualifies - it carries the release
+    /// naming. Music tracks, web pages and bare stream URLs return None so the
+    /// caller falls back to window detection instead of parsing a song title.
+    pub fn parse_target(&self) -> Option<String> {
+        let url = self.url.as_ref()?;
+        let path = url.strip_prefix("file://").unwrap_or(url);
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let name = path.rsplit(['/', '\\']).next().filter(|n| !n.is_empty())?;
+        let decoded = urlencoding::decode(name)
+            .map(|d| d.into_owned())
+            .unwrap_or_else(|_| name.to_string());
+        Some(decoded).filter(|n| has_video_extension(n))
     }
 
-    // MPV Player
-    if title_lower.contains("mpv") {
-        return Some(MediaPlayer::MPV);
+    /// Full local path of the playing file (URL-decoded), if it is a local video file
+    ///
+    /// Folder names carry series/season hints that `parse_target` drops.
+    pub fn local_path(&self) -> Option<String> {
+        let url = self.url.as_ref()?;
+        let path = match url.strip_prefix("file://") {
+            Some(rest) => urlencoding::decode(rest)
+                .map(|d| d.into_owned())
+                .unwrap_or_else(|_| rest.to_string()),
+            None => url.clone(),
+        };
+
+        let bytes = path.as_bytes();
+        let is_absolute = path.starts_with('/')
+            || path.starts_with("\\\\")
+            || (bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':');
+        Some(path).filter(|p| is_absolute && has_video_extension(p))
     }
 
-    // Media Player Classic (MPC-HC, MPC-BE)
-    if title_lower.contains("mpc") || title_lower.contains("media player classic") {
-        return Some(MediaPlayer::MPC);
+    /// Watched fraction (0.0 - 1.0) when both position and duration are known
+    pub fn progress_ratio(&self) -> Option<f64> {
+        match (self.position_secs, self.duration_secs) {
+            (Some(pos), Some(dur)) if dur > 0.0 => Some((pos / dur).clamp(0.0, 1.0)),
+            _ => None,
+        }
     }
+}
 
-    // PotPlayer
-    if title_lower.contains("potplayer") || title_lower.contains("pot player") {
-        return Some(MediaPlayer::PotPlayer);
+/// Common video file extensions shown by players
+const VIDEO_EXTENSIONS: [&str; 8] = [
+    ".mkv", ".mp4", ".avi", ".webm", ".m4v", ".mov", ".wmv", ".flv",
+];
+
+fn has_video_extension(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    VIDEO_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+/// A top-level window as reported by the platform window module
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub title: String,
+    /// Executable name of the owning process (e.g. "vlc.exe", "mpv")
+    pub process_name: Option<String>,
+    /// Window class (Win32 class name, X11 WM_CLASS)
+    pub class_name: Option<String>,
+}
+
+/// A single detection rule
+///
+/// A rule fires when ANY of its matchers hits. Empty lists are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerRule {
+    /// Unique name, reported back when the rule fires
+    pub name: String,
+    pub player: MediaPlayer,
+    /// Higher priority wins when several rules match
+    #[serde(default)]
+    pub priority: i32,
+    /// Title must end with one of these after a separator (" - VLC media player")
+    #[serde(default)]
+    pub title_suffixes: Vec<String>,
+    /// Words/phrases matched anywhere in the title on word boundaries
+    #[serde(default)]
+    pub title_keywords: Vec<String>,
+    /// Case-insensitive regexes matched against the title
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    /// Executable names, without path or ".exe"
+    #[serde(default)]
+    pub process_names: Vec<String>,
+    /// Window class names
+    #[serde(default)]
+    pub window_classes: Vec<String>,
+}
+
+/// Which matcher made a rule fire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    Process,
+    WindowClass,
+    TitleSuffix,
+    TitleKeyword,
+    TitlePattern,
+}
+
+/// Result of matching a window against the rule table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayerMatch {
+    pub player: MediaPlayer,
+    /// Name of the rule that fired
+    pub rule: String,
+    pub priority: i32,
+    pub matched_on: MatchSource,
+}
+
+/// Contents of `player_rules.json`
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    /// Drop the built-in rules instead of adding to them
+    #[serde(default)]
+    replace_defaults: bool,
+    rules: Vec<PlayerRule>,
+}
+
+/// A rule with its regexes compiled once up front
+struct CompiledRule {
+    rule: PlayerRule,
+    keyword_res: Vec<Regex>,
+    pattern_res: Vec<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: PlayerRule) -> Result<Self, String> {
+        let keyword_res = rule
+            .title_keywords
+            .iter()
+            .map(|k| Regex::new(&format!(r"(?i)\b{}\b", regex::escape(k.trim()))))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Rule '{}': invalid keyword: {}", rule.name, e))?;
+        let pattern_res = rule
+            .title_patterns
+            .iter()
+            .map(|p| Regex::new(&format!("(?i){}", p)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Rule '{}': invalid pattern: {}", rule.name, e))?;
+
+        Ok(CompiledRule {
+            rule,
+            keyword_res,
+            pattern_res,
+        })
     }
 
-    // KMPlayer
-    if title_lower.contains("kmplayer") || title_lower.contains("km player") {
-        return Some(MediaPlayer::KMPlayer);
+    /// Check the window against each matcher, strongest evidence first
+    fn matches(&self, window: &WindowInfo) -> Option<MatchSource> {
+        if let Some(ref process) = window.process_name {
+            let process = normalize_process_name(process);
+            if self
+                .rule
+                .process_names
+                .iter()
+                .any(|p| normalize_process_name(p) == process)
+            {
+                return Some(MatchSource::Process);
+            }
+        }
+
+        if let Some(ref class) = window.class_name {
+            if self
+                .rule
+                .window_classes
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(class))
+            {
+                return Some(MatchSource::WindowClass);
+            }
+        }
+
+        if self
+            .rule
+            .title_suffixes
+            .iter()
+            .any(|suffix| title_has_suffix(&window.title, suffix))
+        {
+            return Some(MatchSource::TitleSuffix);
+        }
+
+        if self.keyword_res.iter().any(|re| re.is_match(&window.title)) {
+            return Some(MatchSource::TitleKeyword);
+        }
+
+        if self.pattern_res.iter().any(|re| re.is_match(&window.title)) {
+            return Some(MatchSource::TitlePattern);
+        }
+
+        None
     }
+}
+
+/// "C:\Program Files\VLC\vlc.exe" -> "vlc"
+fn normalize_process_name(name: &str) -> String {
+    let file = name.rsplit(['/', '\\']).next().unwrap_or(name).to_lowercase();
+    file.strip_suffix(".exe").unwrap_or(&file).to_string()
+}
 
-    // GOM Player
-    if title_lower.contains("gom player") || title_lower.contains("gomplayer") {
-        return Some(MediaPlayer::GOM);
+/// True if the title is exactly the suffix or ends with it after a " - " style separator
+fn title_has_suffix(title: &str, suffix: &str) -> bool {
+    let title = title.trim().to_lowercase();
+    let suffix = suffix.trim().to_lowercase();
+
+    match title.strip_suffix(&suffix) {
+        Some(rest) => {
+            let rest = rest.trim_end();
+            rest.is_empty() || rest.ends_with(['-', '–', '—', '|'])
+        }
+        None => false,
     }
+}
 
-    // Windows Media Player
-    if title_lower.contains("windows media player") || title_lower.contains("wmp") {
-        return Some(MediaPlayer::WMP);
+/// Build a built-in rule from string slices
+fn builtin_rule(
+    name: &str,
+    player: MediaPlayer,
+    priority: i32,
+    title_suffixes: &[&str],
+    title_keywords: &[&str],
+    process_names: &[&str],
+    window_classes: &[&str],
+) -> PlayerRule {
+    let to_vec = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+    PlayerRule {
+        name: name.to_string(),
+        player,
+        priority,
+        title_suffixes: to_vec(title_suffixes),
+        title_keywords: to_vec(title_keywords),
+        title_patterns: Vec::new(),
+        process_names: to_vec(process_names),
+        window_classes: to_vec(window_classes),
     }
+}
+
+/// The built-in rule table
+///
+/// Desktop players rank above streaming sites so a local file named after a
+/// site still counts as the player; the file-extension fallback ranks last.
+pub fn default_rules() -> Vec<PlayerRule> {
+    use MediaPlayer::*;
+
+    let mut video_files = builtin_rule("video-file", Generic, 0, &[], &[], &[], &[]);
+    video_files.title_patterns = vec![r"\.(?:mkv|mp4|avi|webm|m4v|mov|wmv|flv)\b".to_string()];
+
+    vec![
+        builtin_rule("vlc", VLC, 20, &["VLC media player", "VLC"], &[], &["vlc"], &["vlc"]),
+        builtin_rule("mpv", MPV, 20, &["mpv"], &[], &["mpv"], &["mpv"]),
+        builtin_rule(
+            "mpc",
+            MPC,
+            20,
+            &["MPC-HC", "MPC-BE", "Media Player Classic"],
+            &["Media Player Classic"],
+            &["mpc-hc", "mpc-hc64", "mpc-be", "mpc-be64"],
+            &["MediaPlayerClassicW"],
+        ),
+        builtin_rule(
+            "potplayer",
+            PotPlayer,
+            20,
+            &[],
+            &["PotPlayer", "Pot Player"],
+            &["potplayer", "potplayer64", "potplayermini", "potplayermini64"],
+            &[],
+        ),
+        builtin_rule(
+            "kmplayer",
+            KMPlayer,
+            20,
+            &[],
+            &["KMPlayer", "KM Player"],
+            &["kmplayer", "kmplayer64x"],
+            &[],
+        ),
+        builtin_rule("gom", GOM, 20, &[], &["GOM Player", "GOMPlayer"], &["gom"], &[]),
+        builtin_rule(
+            "wmp",
+            WMP,
+            20,
+            &["Windows Media Player", "WMP"],
+            &["Windows Media Player"],
+            &["wmplayer"],
+            &[],
+        ),
+        builtin_rule(
+            "linux-players",
+            Generic,
+            20,
+            &["Celluloid", "SMPlayer"],
+            &[],
+            &["celluloid", "smplayer", "haruna", "totem"],
+            &["io.github.celluloid_player.Celluloid", "smplayer", "haruna"],
+        ),
+        builtin_rule("iina", Generic, 20, &[], &[], &["iina"], &[]),
+        builtin_rule(
+            "streaming-sites",
+            Browser,
+            10,
+            &[],
+            &[
+                "YouTube",
+                "Netflix",
+                "Prime Video",
+                "Crunchyroll",
+                "Funimation",
+                "HIDIVE",
+                "HiAnime",
+                "9anime",
+            ],
+            &[],
+            &[],
+        ),
+        video_files,
+    ]
+}
+
+fn compile_rules(rules: Vec<PlayerRule>) -> Result<Vec<CompiledRule>, String> {
+    rules.into_iter().map(CompiledRule::new).collect()
+}
+
+lazy_static::lazy_static! {
+    static ref RULES: RwLock<Vec<CompiledRule>> =
+        RwLock::new(compile_rules(default_rules()).expect("built-in player rules must compile"));
+}
+
+/// Parse a rules file and merge it with the built-in rules
+///
+/// Returns the full rule table, or an error naming the broken rule.
+pub fn parse_rules(json: &str) -> Result<Vec<PlayerRule>, String> {
+    let file: RulesFile =
+        serde_json::from_str(json).map_err(|e| format!("Invalid player rules file: {}", e))?;
+
+    let mut rules = if file.replace_defaults {
+        Vec::new()
+    } else {
+        default_rules()
+    };
 
-    // Browser-based media (YouTube, Netflix, etc.)
-    if title_lower.contains("youtube")
-        || title_lower.contains("netflix")
-        || title_lower.contains("prime video")
-        || title_lower.contains("crunchyroll")
-        || title_lower.contains("funimation")
-        || title_lower.contains("hidive")
-        || title_lower.contains("hianime")
-        || title_lower.contains("9anime")
-    {
-        return Some(MediaPlayer::Browser);
+    for rule in file.rules {
+        // A user rule with a built-in's name replaces it
+        rules.retain(|r| r.name != rule.name);
+        rules.push(rule);
     }
 
-    // Generic detection: Check for common video file extensions in title
-    // Many players show the filename in the window title
-    let video_extensions = [
-        ".mkv", ".mp4", ".avi", ".webm", ".m4v", ".mov", ".wmv", ".flv",
-    ];
-    for ext in &video_extensions {
-        if title_lower.contains(ext) {
-            return Some(MediaPlayer::Generic);
+    Ok(rules)
+}
+
+/// Replace the active rule table (rules are validated before swapping)
+pub fn set_rules(rules: Vec<PlayerRule>) -> Result<(), String> {
+    let compiled = compile_rules(rules)?;
+    let mut active = RULES.write().map_err(|_| "Rules lock error")?;
+    *active = compiled;
+    Ok(())
+}
+
+/// Get the active rule table
+pub fn get_rules() -> Vec<PlayerRule> {
+    RULES
+        .read()
+        .map(|rules| rules.iter().map(|r| r.rule.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Load rules from a JSON file, falling back to the built-ins if it doesn't exist
+///
+/// Returns the number of active rules.
+pub fn load_rules_file(path: &Path) -> Result<usize, String> {
+    let rules = if path.exists() {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        parse_rules(&json)?
+    } else {
+        default_rules()
+    };
+
+    let count = rules.len();
+    set_rules(rules)?;
+    Ok(count)
+}
+
+fn match_with_rules(rules: &[CompiledRule], window: &WindowInfo) -> Option<PlayerMatch> {
+    let mut best: Option<PlayerMatch> = None;
+
+    for compiled in rules {
+        if let Some(source) = compiled.matches(window) {
+            // Ties keep the earlier rule
+            if best
+                .as_ref()
+                .is_some_and(|b| b.priority >= compiled.rule.priority)
+            {
+                continue;
+            }
+            best = Some(PlayerMatch {
+                player: compiled.rule.player,
+                rule: compiled.rule.name.clone(),
+                priority: compiled.rule.priority,
+                matched_on: source,
+            });
         }
     }
 
-    // Not a known media player - ignore
-    None
+    best
+}
+
+/// Match a window against the active rule table
+///
+/// Returns the highest-priority rule that fired, or None for non-media
+/// windows (VS Code, File Explorer, etc.)
+pub fn match_media_player(window: &WindowInfo) -> Option<PlayerMatch> {
+    let rules = RULES.read().ok()?;
+    match_with_rules(&rules, window)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    impl WindowInfo {
+        /// Window info with only a title (process and class unknown)
+        pub fn from_title(title: &str) -> Self {
+            WindowInfo {
+                title: title.to_string(),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Title-only detection, as the old substring matcher did
+    fn detect_media_player(title: &str) -> Option<MediaPlayer> {
+        match_media_player(&WindowInfo::from_title(title)).map(|m| m.player)
+    }
+
     #[test]
     fn test_vlc_detection() {
         assert_eq!(
@@ -116,18 +516,131 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_playback_parse_target_requires_video_file() {
+        let info = PlaybackInfo {
+            source: "mpris".to_string(),
+            player: MediaPlayer::MPV,
+            url: Some("file:///home/me/Anime/%5BSubsPlease%5D%20Frieren%20-%2005%20%5B1080p%5D.mkv".to_string()),
+            title: Some("Frieren - 05".to_string()),
+            position_secs: Some(600.0),
+            duration_secs: Some(1440.0),
+            state: PlaybackState::Playing,
+        };
+        assert_eq!(
+            info.parse_target(),
+            Some("[SubsPlease] Frieren - 05 [1080p].mkv".to_string())
+        );
+
+        let stream = PlaybackInfo {
+            url: Some("https://example.com/stream?id=1".to_string()),
+            ..info
+        };
+        assert_eq!(stream.parse_target(), None);
+
+        let music = PlaybackInfo {
+            url: Some("file:///home/me/Music/Opening.flac".to_string()),
+            ..stream
+        };
+        assert_eq!(music.parse_target(), None);
+    }
+
     #[test]
     fn test_non_media_window() {
         assert_eq!(detect_media_player("Visual Studio Code"), None);
         assert_eq!(detect_media_player("File Explorer"), None);
     }
+
+    #[test]
+    fn test_word_boundaries_avoid_false_positives() {
+        assert_eq!(detect_media_player("wmpd settings"), None);
+        assert_eq!(
+            detect_media_player("How to install VLC on Ubuntu - Stack Overflow - Mozilla Firefox"),
+            None
+        );
+        assert_eq!(detect_media_player("mpvpaper config - Kate"), None);
+    }
+
+    #[test]
+    fn test_process_and_class_matching() {
+        let rules = compile_rules(default_rules()).unwrap();
+
+        let window = WindowInfo {
+            title: "Frieren - 05.mkv".to_string(),
+            process_name: Some(r"C:\Program Files\MPC-HC\mpc-hc64.exe".to_string()),
+            class_name: None,
+        };
+        let found = match_with_rules(&rules, &window).unwrap();
+        assert_eq!(found.player, MediaPlayer::MPC);
+        assert_eq!(found.rule, "mpc");
+        assert_eq!(found.matched_on, MatchSource::Process);
+
+        let window = WindowInfo {
+            title: "Celluloid".to_string(),
+            process_name: None,
+            class_name: Some("io.github.celluloid_player.Celluloid".to_string()),
+        };
+        let found = match_with_rules(&rules, &window).unwrap();
+        assert_eq!(found.rule, "linux-players");
+        assert_eq!(found.matched_on, MatchSource::WindowClass);
+    }
+
+    #[test]
+    fn test_priority_prefers_player_over_site_and_extension() {
+        let found = match_media_player(&WindowInfo::from_title(
+            "Crunchyroll rip - 01.mkv - VLC media player",
+        ))
+        .unwrap();
+        assert_eq!(found.player, MediaPlayer::VLC);
+        assert_eq!(found.rule, "vlc");
+        assert_eq!(found.matched_on, MatchSource::TitleSuffix);
+    }
+
+    #[test]
+    fn test_custom_rules_file() {
+        let json = r#"{
+            "rules": [
+                {
+                    "name": "animelon",
+                    "player": "Browser",
+                    "priority": 15,
+                    "title_keywords": ["Animelon"]
+                },
+                {
+                    "name": "vlc",
+                    "player": "VLC",
+                    "priority": 20,
+                    "process_names": ["vlc"]
+                }
+            ]
+        }"#;
+        let rules = parse_rules(json).unwrap();
+        assert_eq!(rules.len(), default_rules().len() + 1);
+
+        let compiled = compile_rules(rules).unwrap();
+        let found =
+            match_with_rules(&compiled, &WindowInfo::from_title("Frieren 5 | Animelon - Firefox"))
+                .unwrap();
+        assert_eq!(found.rule, "animelon");
+
+        // The overridden vlc rule no longer matches on the title suffix
+        let found = match_with_rules(&compiled, &WindowInfo::from_title("Movie - VLC media player"));
+        assert!(found.is_none());
+
+        let bad = r#"{"rules": [{"name": "bad", "player": "VLC", "title_patterns": ["("]}]}"#;
+        assert!(compile_rules(parse_rules(bad).unwrap()).is_err());
+    }
 }
 
 #[test]
 fn test_chrome_hianime_detection() {
     let title =
         "Chitose Is In The Ramune Bottle Episode 1 English Sub at Hianime - Google Chrome – Suraj";
-    let result = detect_media_player(title);
+    let window = WindowInfo {
+        title: title.to_string(),
+        ..Default::default()
+    };
+    let result = match_media_player(&window).map(|m| m.player);
     println!("Detection result for Chrome/Hianime title: {:?}", result);
     assert!(result.is_some(), "Should detect as browser");
     assert_eq!(result, Some(MediaPlayer::Browser));
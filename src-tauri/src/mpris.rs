@@ -0,0 +1,333 @@
+//! MPRIS Now-Playing Module
+//!
+//! PURPOSE: Read playback state from Linux media players over the D-Bus
+//! session bus using the MPRIS2 specification
+//! (`org.mpris.MediaPlayer2.Player` Metadata, PlaybackStatus, Position)
+//!
+//! mpv (with mpv-mpris), VLC, Celluloid and browsers all publish these
+//! interfaces, which gives us the exact file URL, title, position and duration
+//! instead of guessing from window titles.
+#![cfg(target_os = "linux")]
+
+use crate::media_player::{MediaPlayer, PlaybackInfo, PlaybackState};
+use std::collections::HashMap;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::Connection;
+
+/// Well-known bus name prefix every MPRIS player registers under
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+#[zbus::proxy(
+    interface = "org.mpris.MediaPlayer2.Player",
+    default_path = "/org/mpris/MediaPlayer2"
+)]
+trait Player {
+    #[zbus(property)]
+    fn metadata(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn position(&self) -> zbus::Result<i64>;
+}
+
+/// Get the most relevant MPRIS player on the session bus
+///
+/// See `pick_now_playing` for how players are ranked.
+/// Returns None if there is no session bus or no player with a video loaded.
+pub async fn get_now_playing() -> Option<PlaybackInfo> {
+    let conn = match Connection::session().await {
+        Ok(c) => c,
+        Err(e) => {
+            println!("[MPRIS] Session bus unavailable: {}", e);
+            return None;
+        }
+    };
+
+    pick_now_playing(get_all_players(&conn).await)
+}
+
+/// Query every MPRIS player registered on the given bus
+pub async fn get_all_players(conn: &Connection) -> Vec<PlaybackInfo> {
+    let names = match list_player_names(conn).await {
+        Ok(n) => n,
+        Err(e) => {
+            println!("[MPRIS] Failed to list bus names: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut players = Vec::new();
+    for name in names {
+        match query_player(conn, &name).await {
+            Ok(Some(info)) => players.push(info),
+            Ok(None) => {}
+            Err(e) => println!("[MPRIS] Failed to query {}: {}", name, e),
+        }
+    }
+    players
+}
+
+/// Choose the player to report
+///
+/// Only players showing a video file count: Generic players (music apps) and
+/// players on web pages or audio tracks are skipped. A local video file beats
+/// a remote one, then Playing beats Paused; Stopped players are ignored.
+/// Bus names are listed alphabetically, so without this a browser or music
+/// player would win over mpv/vlc just by sorting first.
+fn pick_now_playing(players: Vec<PlaybackInfo>) -> Option<PlaybackInfo> {
+    players
+        .into_iter()
+        .filter(|info| info.player != MediaPlayer::Generic && info.parse_target().is_some())
+        .filter_map(|info| {
+            let state_rank = match info.state {
+                PlaybackState::Playing => 0,
+                PlaybackState::Paused => 1,
+                PlaybackState::Stopped => return None,
+            };
+            Some(((info.local_path().is_none(), state_rank), info))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, info)| info)
+}
+
+async fn list_player_names(conn: &Connection) -> zbus::Result<Vec<String>> {
+    let dbus = zbus::fdo::DBusProxy::new(conn).await?;
+    let names = dbus.list_names().await?;
+
+    let mut players: Vec<String> = names
+        .into_iter()
+        .map(|n| n.to_string())
+        .filter(|n| n.starts_with(MPRIS_PREFIX))
+        .collect();
+    players.sort();
+    Ok(players)
+}
+
+/// Read Metadata, PlaybackStatus and Position from a single player
+///
+/// Returns Ok(None) when the player has nothing loaded.
+async fn query_player(conn: &Connection, bus_name: &str) -> zbus::Result<Option<PlaybackInfo>> {
+    // Position never emits PropertiesChanged, so caching would return stale values
+    let proxy = PlayerProxy::builder(conn)
+        .destination(bus_name.to_string())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    let metadata = proxy.metadata().await?;
+    let state = parse_playback_status(&proxy.playback_status().await?);
+    // Some players (e.g. browsers on live streams) don't implement Position
+    let position_us = proxy.position().await.ok();
+
+    let url = metadata.get("xesam:url").and_then(value_as_string);
+    let title = metadata.get("xesam:title").and_then(value_as_string);
+    if url.is_none() && title.is_none() {
+        return Ok(None);
+    }
+
+    let duration_us = metadata.get("mpris:length").and_then(value_as_i64);
+
+    Ok(Some(PlaybackInfo {
+        source: "mpris".to_string(),
+        player: player_from_bus_name(bus_name),
+        url,
+        title,
+        position_secs: position_us.map(micros_to_secs),
+        duration_secs: duration_us.filter(|&d| d > 0).map(micros_to_secs),
+        state,
+    }))
+}
+
+fn parse_playback_status(status: &str) -> PlaybackState {
+    match status {
+        "Playing" => PlaybackState::Playing,
+        "Paused" => PlaybackState::Paused,
+        _ => PlaybackState::Stopped,
+    }
+}
+
+/// Map an MPRIS bus name (e.g. `org.mpris.MediaPlayer2.vlc`) to a MediaPlayer
+///
+/// Players may append an instance suffix like `.instance1234`, so only the
+/// first segment after the prefix is considered.
+fn player_from_bus_name(bus_name: &str) -> MediaPlayer {
+    let id = bus_name
+        .strip_prefix(MPRIS_PREFIX)
+        .unwrap_or(bus_name)
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match id.as_str() {
+        "vlc" => MediaPlayer::VLC,
+        "mpv" => MediaPlayer::MPV,
+        "chromium" | "chrome" | "firefox" | "brave" | "vivaldi" | "opera" | "edge" | "zen" => {
+            MediaPlayer::Browser
+        }
+        _ => MediaPlayer::Generic,
+    }
+}
+
+fn micros_to_secs(us: i64) -> f64 {
+    us as f64 / 1_000_000.0
+}
+
+fn value_as_string(value: &OwnedValue) -> Option<String> {
+    match &**value {
+        Value::Str(s) if !s.is_empty() => Some(s.to_string()),
+        Value::ObjectPath(p) => Some(p.to_string()),
+        _ => None,
+    }
+}
+
+/// mpris:length is specified as int64, but some players send uint64 or double
+fn value_as_i64(value: &OwnedValue) -> Option<i64> {
+    match &**value {
+        Value::I64(n) => Some(*n),
+        Value::U64(n) => i64::try_from(*n).ok(),
+        Value::I32(n) => Some(*n as i64),
+        Value::U32(n) => Some(*n as i64),
+        Value::F64(n) => Some(*n as i64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command, Stdio};
+    use zbus::interface;
+
+    /// Fake MPRIS player served on a private bus
+    struct FakePlayer {
+        url: String,
+        status: String,
+    }
+
+    #[interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl FakePlayer {
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            let mut map = HashMap::new();
+            map.insert(
+                "xesam:url".to_string(),
+                OwnedValue::try_from(Value::from(self.url.as_str())).unwrap(),
+            );
+            map.insert(
+                "xesam:title".to_string(),
+                OwnedValue::try_from(Value::from("Frieren - 05")).unwrap(),
+            );
+            map.insert("mpris:length".to_string(), OwnedValue::from(1_440_000_000i64));
+            map
+        }
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.clone()
+        }
+
+        #[zbus(property)]
+        fn position(&self) -> i64 {
+            720_000_000
+        }
+    }
+
+    /// Start a private dbus-daemon, returning the process and its address
+    fn spawn_private_bus() -> Option<(Child, String)> {
+        let mut child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+
+        use std::io::{BufRead, BufReader};
+        let mut line = String::new();
+        BufReader::new(child.stdout.take()?).read_line(&mut line).ok()?;
+        Some((child, line.trim().to_string()))
+    }
+
+    #[test]
+    fn test_player_from_bus_name() {
+        assert_eq!(player_from_bus_name("org.mpris.MediaPlayer2.vlc"), MediaPlayer::VLC);
+        assert_eq!(
+            player_from_bus_name("org.mpris.MediaPlayer2.firefox.instance_1_42"),
+            MediaPlayer::Browser
+        );
+        assert_eq!(
+            player_from_bus_name("org.mpris.MediaPlayer2.io.github.celluloid_player.Celluloid"),
+            MediaPlayer::Generic
+        );
+    }
+
+    #[test]
+    fn test_pick_prefers_local_video_over_browser_and_music() {
+        let playing = |player, url: &str| PlaybackInfo {
+            source: "mpris".to_string(),
+            player,
+            url: Some(url.to_string()),
+            title: Some("Some Title".to_string()),
+            position_secs: None,
+            duration_secs: None,
+            state: PlaybackState::Playing,
+        };
+        let mut mpv = playing(MediaPlayer::MPV, "file:///anime/Frieren%20-%2005.mkv");
+        mpv.state = PlaybackState::Paused;
+
+        // Alphabetical bus order: chromium, mpv, spotify
+        let players = vec![
+            playing(MediaPlayer::Browser, "https://www.youtube.com/watch?v=abc"),
+            mpv.clone(),
+            playing(MediaPlayer::Generic, "file:///music/song.mkv"),
+        ];
+        assert_eq!(pick_now_playing(players), Some(mpv));
+
+        let music_only = vec![playing(MediaPlayer::VLC, "file:///music/song.flac")];
+        assert_eq!(pick_now_playing(music_only), None);
+    }
+
+    #[tokio::test]
+    async fn test_reads_fake_player_on_private_bus() {
+        let Some((mut daemon, address)) = spawn_private_bus() else {
+            println!("dbus-daemon not available, skipping");
+            return;
+        };
+
+        let player = FakePlayer {
+            url: "file:///anime/Frieren%20-%2005%20%5B1080p%5D.mkv".to_string(),
+            status: "Playing".to_string(),
+        };
+        let _server = zbus::connection::Builder::address(address.as_str())
+            .unwrap()
+            .name("org.mpris.MediaPlayer2.mpv")
+            .unwrap()
+            .serve_at("/org/mpris/MediaPlayer2", player)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let client = zbus::connection::Builder::address(address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let info = pick_now_playing(get_all_players(&client).await);
+        let _ = daemon.kill();
+
+        let info = info.expect("fake player should be found");
+        assert_eq!(info.player, MediaPlayer::MPV);
+        assert_eq!(info.state, PlaybackState::Playing);
+        assert_eq!(info.position_secs, Some(720.0));
+        assert_eq!(info.duration_secs, Some(1440.0));
+        assert_eq!(info.progress_ratio(), Some(0.5));
+        assert_eq!(
+            info.parse_target(),
+            Some("Frieren - 05 [1080p].mkv".to_string())
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! mpv JSON IPC Module
+//!
+//! PURPOSE: Track mpv playback precisely through its `--input-ipc-server` socket
+//! (a Unix socket on Linux/macOS, a named pipe on Windows)
+//!
+//! APPROACH: A background watcher connects to the configured socket, subscribes
+//! to `path`, `media-title`, `time-pos`, `duration` and `pause` with
+//! `observe_property`, and keeps the latest values in memory. Detection reads
+//! that snapshot instead of parsing the window title.
+//!
+//! Users enable it by starting mpv with e.g. `--input-ipc-server=/tmp/mpvsocket`
+//! (or `input-ipc-server=...` in mpv.conf). A custom socket path is kept in
+//! `mpv_ipc.json`.
+
+use crate::media_player::{MediaPlayer, PlaybackInfo, PlaybackState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Default socket path, matching the example in the mpv manual
+#[cfg(unix)]
+pub const DEFAULT_SOCKET_PATH: &str = "/tmp/mpvsocket";
+#[cfg(windows)]
+pub const DEFAULT_SOCKET_PATH: &str = r"\\.\pipe\mpvsocket";
+
+/// How long to wait before retrying when mpv isn't running
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Properties observed on connect, indexed by their observe id - 1
+const OBSERVED_PROPERTIES: [&str; 5] = ["path", "media-title", "time-pos", "duration", "pause"];
+
+lazy_static::lazy_static! {
+    static ref SOCKET_PATH: Mutex<String> = Mutex::new(load_config_file().socket_path());
+    static ref MPV_STATE: Mutex<MpvState> = Mutex::new(MpvState::default());
+}
+
+/// Latest property values reported by mpv
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MpvState {
+    pub connected: bool,
+    pub path: Option<String>,
+    pub media_title: Option<String>,
+    pub time_pos: Option<f64>,
+    pub duration: Option<f64>,
+    pub pause: Option<bool>,
+}
+
+impl MpvState {
+    /// Apply one line received from mpv (property-change or end-file events)
+    ///
+    /// Command replies (`{"request_id": .., "error": ..}`) are ignored.
+    pub fn apply_message(&mut self, msg: &Value) {
+        match msg.get("event").and_then(|e| e.as_str()) {
+            Some("property-change") => {
+                let data = msg.get("data").unwrap_or(&Value::Null);
+                match msg.get("name").and_then(|n| n.as_str()) {
+                    Some("path") => self.path = data.as_str().map(String::from),
+                    Some("media-title") => self.media_title = data.as_str().map(String::from),
+                    Some("time-pos") => self.time_pos = data.as_f64(),
+                    Some("duration") => self.duration = data.as_f64(),
+                    Some("pause") => self.pause = data.as_bool(),
+                    _ => {}
+                }
+            }
+            Some("end-file") | Some("idle") => {
+                self.path = None;
+                self.media_title = None;
+                self.time_pos = None;
+                self.duration = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Convert to the shared playback structure, or None if nothing is loaded
+    pub fn to_playback_info(&self) -> Option<PlaybackInfo> {
+        if !self.connected || (self.path.is_none() && self.media_title.is_none()) {
+            return None;
+        }
+
+        let state = if self.pause.unwrap_or(false) {
+            PlaybackState::Paused
+        } else {
+            PlaybackState::Playing
+        };
+
+        Some(PlaybackInfo {
+            source: "mpv-ipc".to_string(),
+            player: MediaPlayer::MPV,
+            url: self.path.clone(),
+            title: self.media_title.clone(),
+            position_secs: self.time_pos,
+            duration_secs: self.duration,
+            state,
+        })
+    }
+}
+
+/// Get what mpv is currently playing, if the watcher is connected
+pub fn get_now_playing() -> Option<PlaybackInfo> {
+    MPV_STATE.lock().ok()?.to_playback_info()
+}
+
+/// Settings as stored in `mpv_ipc.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+struct MpvIpcConfig {
+    /// None means the platform default, so it follows DEFAULT_SOCKET_PATH
+    socket_path: Option<String>,
+}
+
+impl MpvIpcConfig {
+    fn new(path: Option<String>) -> Self {
+        MpvIpcConfig {
+            socket_path: path.filter(|p| !p.trim().is_empty()),
+        }
+    }
+
+    fn socket_path(&self) -> String {
+        self.socket_path
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SOCKET_PATH.to_string())
+    }
+}
+
+fn config_path() -> PathBuf {
+    crate::app_data_dir().join("mpv_ipc.json")
+}
+
+fn load_config_file() -> MpvIpcConfig {
+    let json = match std::fs::read_to_string(config_path()) {
+        Ok(json) => json,
+        Err(_) => return MpvIpcConfig::default(),
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("[mpv IPC] Ignoring unreadable settings file: {}", e);
+        MpvIpcConfig::default()
+    })
+}
+
+fn save_config_file(config: &MpvIpcConfig) -> Result<(), String> {
+    let path = config_path();
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to encode mpv IPC settings: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Change and save the IPC socket path; the watcher reconnects on its next check
+///
+/// None or an empty path restores the default.
+pub fn set_socket_path(path: Option<String>) -> Result<(), String> {
+    let config = MpvIpcConfig::new(path);
+    let mut current = SOCKET_PATH.lock().map_err(|_| "Socket path lock error")?;
+    save_config_file(&config)?;
+    *current = config.socket_path();
+    Ok(())
+}
+
+pub fn get_socket_path() -> String {
+    SOCKET_PATH
+        .lock()
+        .map(|p| p.clone())
+        .unwrap_or_else(|_| DEFAULT_SOCKET_PATH.to_string())
+}
+
+/// Background task: keep a connection to mpv open, reconnecting as needed
+pub async fn run_watcher() {
+    loop {
+        let path = get_socket_path();
+
+        match connect(&path).await {
+            Ok(stream) => {
+                println!("[mpv IPC] Connected to {}", path);
+                let result = run_session(stream, &path, &MPV_STATE).await;
+                println!("[mpv IPC] Disconnected from {}: {:?}", path, result);
+            }
+            Err(_) => {
+                // mpv not running (or IPC not enabled) - this is the normal case
+            }
+        }
+
+        if let Ok(mut state) = MPV_STATE.lock() {
+            *state = MpvState::default();
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[cfg(unix)]
+async fn connect(path: &str) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn connect(
+    path: &str,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(path)
+}
+
+/// Subscribe to the observed properties and apply events until the socket
+/// closes or the configured path changes
+async fn run_session<S>(stream: S, path: &str, state: &Mutex<MpvState>) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    for (i, name) in OBSERVED_PROPERTIES.iter().enumerate() {
+        let command = json!({ "command": ["observe_property", i + 1, name] });
+        writer
+            .write_all(format!("{}\n", command).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send observe_property: {}", e))?;
+    }
+
+    if let Ok(mut s) = state.lock() {
+        s.connected = true;
+    }
+
+    let mut path_check = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(l)) => l,
+                    Ok(None) => return Ok(()),
+                    Err(e) => return Err(format!("Read error: {}", e)),
+                };
+
+                if let Ok(msg) = serde_json::from_str::<Value>(&line) {
+                    if let Ok(mut s) = state.lock() {
+                        s.apply_message(&msg);
+                    }
+                }
+            }
+            _ = path_check.tick() => {
+                if get_socket_path() != path {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_property_changes() {
+        let mut state = MpvState {
+            connected: true,
+            ..Default::default()
+        };
+
+        state.apply_message(&json!({"event": "property-change", "id": 1, "name": "path", "data": "/anime/Frieren - 05.mkv"}));
+        state.apply_message(&json!({"event": "property-change", "id": 3, "name": "time-pos", "data": 300.5}));
+        state.apply_message(&json!({"event": "property-change", "id": 4, "name": "duration", "data": 1420.0}));
+        state.apply_message(&json!({"event": "property-change", "id": 5, "name": "pause", "data": true}));
+        state.apply_message(&json!({"request_id": 0, "error": "success"}));
+
+        let info = state.to_playback_info().unwrap();
+        assert_eq!(info.player, MediaPlayer::MPV);
+        assert_eq!(info.state, PlaybackState::Paused);
+        assert_eq!(info.position_secs, Some(300.5));
+        assert_eq!(info.parse_target(), Some("Frieren - 05.mkv".to_string()));
+
+        state.apply_message(&json!({"event": "end-file"}));
+        assert!(state.to_playback_info().is_none());
+    }
+
+    #[test]
+    fn test_config_round_trip_and_default() {
+        let custom = MpvIpcConfig::new(Some("/run/user/1000/mpv".to_string()));
+        let json = serde_json::to_string(&custom).unwrap();
+        let loaded: MpvIpcConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.socket_path(), "/run/user/1000/mpv");
+
+        assert_eq!(
+            MpvIpcConfig::new(Some("  ".to_string())),
+            MpvIpcConfig::default()
+        );
+        assert_eq!(MpvIpcConfig::default().socket_path(), DEFAULT_SOCKET_PATH);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_session_with_fake_mpv_socket() {
+        use tokio::net::UnixListener;
+
+        let socket_path = std::env::temp_dir().join(format!("playon-mpv-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        // Fake mpv: read the observe commands, then report a loaded file and close
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut lines = BufReader::new(reader).lines();
+
+            let mut observed = Vec::new();
+            for _ in 0..OBSERVED_PROPERTIES.len() {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let msg: Value = serde_json::from_str(&line).unwrap();
+                observed.push(msg["command"][2].as_str().unwrap().to_string());
+            }
+
+            let events = [
+                json!({"event": "property-change", "id": 1, "name": "path", "data": "/anime/[SubsPlease] Frieren - 05 (1080p).mkv"}),
+                json!({"event": "property-change", "id": 2, "name": "media-title", "data": "Frieren - 05"}),
+                json!({"event": "property-change", "id": 3, "name": "time-pos", "data": 600.0}),
+                json!({"event": "property-change", "id": 4, "name": "duration", "data": 1440.0}),
+                json!({"event": "property-change", "id": 5, "name": "pause", "data": false}),
+            ];
+            for event in events {
+                writer.write_all(format!("{}\n", event).as_bytes()).await.unwrap();
+            }
+            observed
+        });
+
+        let state = Mutex::new(MpvState::default());
+        let stream = connect(socket_path.to_str().unwrap()).await.unwrap();
+        // Point the in-memory path at the fake socket without saving it
+        let previous = std::mem::replace(
+            &mut *SOCKET_PATH.lock().unwrap(),
+            socket_path.to_string_lossy().to_string(),
+        );
+        let result = run_session(stream, &get_socket_path(), &state).await;
+        *SOCKET_PATH.lock().unwrap() = previous;
+        let _ = std::fs::remove_file(&socket_path);
+
+        assert!(result.is_ok());
+        assert_eq!(server.await.unwrap(), OBSERVED_PROPERTIES.to_vec());
+
+        let info = state.lock().unwrap().to_playback_info().unwrap();
+        assert_eq!(info.state, PlaybackState::Playing);
+        assert_eq!(info.progress_ratio(), Some(600.0 / 1440.0));
+        assert_eq!(info.title, Some("Frieren - 05".to_string()));
+    }
+}
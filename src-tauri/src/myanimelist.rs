@@ -70,16 +70,6 @@ pub struct MalAnimeListStatus {
     pub is_rewatching: Option<bool>,
 }
 
-/// List status for manga
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MalMangaListStatus {
-    pub status: Option<String>,
-    pub score: Option<i32>,
-    pub num_chapters_read: Option<i32>,
-    pub num_volumes_read: Option<i32>,
-    pub is_rereading: Option<bool>,
-}
-
 /// Response when updating list entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MalListUpdateResponse {
@@ -388,6 +378,42 @@ pub async fn search_manga(
 // LIST UPDATE API
 // ============================================================================
 
+#[derive(Debug, Deserialize)]
+struct MalAnimeDetails {
+    my_list_status: Option<MalAnimeListStatus>,
+}
+
+/// Get the user's list status for an anime
+///
+/// # Returns
+/// * `Ok(None)` when the anime isn't on the user's list
+pub async fn get_anime_list_status(
+    access_token: &str,
+    anime_id: i64,
+) -> Result<Option<MalAnimeListStatus>, String> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/anime/{}", MAL_API_URL, anime_id))
+        .header("Authorization", format!("Bearer {}", access_token))
+        .query(&[("fields", "my_list_status")])
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to get list status: {}", error_text));
+    }
+
+    let details: MalAnimeDetails = response
+        .json()
+        .await
+        .map_err(|e| format!("Parse error: {}", e))?;
+
+    Ok(details.my_list_status)
+}
+
 /// Update anime progress on MAL
 ///
 /// # Arguments
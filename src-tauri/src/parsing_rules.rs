@@ -0,0 +1,283 @@
+//! User Parsing Rules Module
+//!
+//! PURPOSE: Let users fix title parsing without waiting for a release, using
+//! `parsing_rules.json` in the app data directory
+//!
+//! APPROACH: The whole file is validated (every regex must compile) before it
+//! replaces the active rules, so a typo keeps the previous rules in place.
+//! `title_parser` reads the active rules on every parse; `lib.rs` polls the
+//! file and reloads it when it changes.
+//!
+//! ```json
+//! {
+//!   "player_suffixes": [" - Kodi"],
+//!   "patterns": ["^(?P<title>.+?) #(?P<episode>\\d+)"],
+//!   "title_rewrites": { "Oshi no Ko": "[Oshi no Ko]" },
+//!   "ignore_patterns": ["trailer", "^Netflix$"]
+//! }
+//! ```
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Named groups a custom pattern may use
+const PATTERN_GROUPS: [&str; 3] = ["title", "episode", "season"];
+
+/// Contents of `parsing_rules.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsingRules {
+    /// Extra player suffixes stripped from window titles (" - Kodi")
+    #[serde(default)]
+    pub player_suffixes: Vec<String>,
+    /// Case-insensitive regexes with named groups `title`, `episode`, `season`,
+    /// tried before the built-in strategies
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Parsed title → title to use instead (matched case-insensitively)
+    #[serde(default)]
+    pub title_rewrites: BTreeMap<String, String>,
+    /// Case-insensitive regexes; matching window titles are never treated as media
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+}
+
+impl ParsingRules {
+    /// Number of entries across all sections
+    pub fn count(&self) -> usize {
+        self.player_suffixes.len()
+            + self.patterns.len()
+            + self.title_rewrites.len()
+            + self.ignore_patterns.len()
+    }
+}
+
+/// Rules with their regexes compiled once up front
+#[derive(Default)]
+struct CompiledParsingRules {
+    rules: ParsingRules,
+    patterns: Vec<Regex>,
+    ignore: Vec<Regex>,
+    /// (lowercased from, to)
+    rewrites: Vec<(String, String)>,
+}
+
+impl CompiledParsingRules {
+    fn new(rules: ParsingRules) -> Result<Self, String> {
+        let mut patterns = Vec::new();
+        for (i, pattern) in rules.patterns.iter().enumerate() {
+            let re = Regex::new(&format!("(?i){}", pattern))
+                .map_err(|e| format!("Pattern #{} is invalid: {}", i + 1, e))?;
+
+            let groups: Vec<&str> = re.capture_names().flatten().collect();
+            if let Some(unknown) = groups.iter().find(|g| !PATTERN_GROUPS.contains(g)) {
+                return Err(format!(
+                    "Pattern #{} has unknown group '{}' (use title, episode or season)",
+                    i + 1,
+                    unknown
+                ));
+            }
+            if !groups.contains(&"title") && !groups.contains(&"episode") {
+                return Err(format!(
+                    "Pattern #{} needs a 'title' or 'episode' group",
+                    i + 1
+                ));
+            }
+            patterns.push(re);
+        }
+
+        let ignore = rules
+            .ignore_patterns
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                Regex::new(&format!("(?i){}", p))
+                    .map_err(|e| format!("Ignore pattern #{} is invalid: {}", i + 1, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(suffix) = rules.player_suffixes.iter().find(|s| s.trim().is_empty()) {
+            return Err(format!("Empty player suffix '{}'", suffix));
+        }
+
+        let mut rewrites = Vec::new();
+        for (from, to) in &rules.title_rewrites {
+            if from.trim().is_empty() || to.trim().is_empty() {
+                return Err(format!("Title rewrite '{}' → '{}' is empty", from, to));
+            }
+            rewrites.push((from.trim().to_lowercase(), to.trim().to_string()));
+        }
+
+        Ok(CompiledParsingRules {
+            rules,
+            patterns,
+            ignore,
+            rewrites,
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref RULES: RwLock<CompiledParsingRules> = RwLock::new(CompiledParsingRules::default());
+}
+
+/// Parse and validate the rules file contents
+pub fn parse_rules(json: &str) -> Result<ParsingRules, String> {
+    let rules: ParsingRules =
+        serde_json::from_str(json).map_err(|e| format!("Invalid parsing rules file: {}", e))?;
+    CompiledParsingRules::new(rules.clone())?;
+    Ok(rules)
+}
+
+/// Replace the active rules (rules are validated before swapping)
+pub fn set_rules(rules: ParsingRules) -> Result<(), String> {
+    let compiled = CompiledParsingRules::new(rules)?;
+    let mut active = RULES.write().map_err(|_| "Rules lock error")?;
+    *active = compiled;
+    Ok(())
+}
+
+/// Get the active rules
+pub fn get_rules() -> ParsingRules {
+    RULES
+        .read()
+        .map(|rules| rules.rules.clone())
+        .unwrap_or_default()
+}
+
+/// Read and validate a rules file without applying it; a missing file is empty
+pub fn check_rules_file(path: &Path) -> Result<ParsingRules, String> {
+    if !path.exists() {
+        return Ok(ParsingRules::default());
+    }
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_rules(&json)
+}
+
+/// Load rules from a JSON file (no file means no user rules)
+///
+/// Returns the number of rule entries.
+pub fn load_rules_file(path: &Path) -> Result<usize, String> {
+    let rules = check_rules_file(path)?;
+    let count = rules.count();
+    set_rules(rules)?;
+    Ok(count)
+}
+
+/// User player suffixes, stripped along with the built-in ones
+pub fn player_suffixes() -> Vec<String> {
+    RULES
+        .read()
+        .map(|rules| rules.rules.player_suffixes.clone())
+        .unwrap_or_default()
+}
+
+/// Compiled custom patterns, in file order
+pub fn custom_patterns() -> Vec<Regex> {
+    RULES
+        .read()
+        .map(|rules| rules.patterns.clone())
+        .unwrap_or_default()
+}
+
+/// The user's replacement for a parsed title, if there is one
+pub fn rewrite_title(title: &str) -> Option<String> {
+    let key = title.trim().to_lowercase();
+    let rules = RULES.read().ok()?;
+    rules
+        .rewrites
+        .iter()
+        .find(|(from, _)| *from == key)
+        .map(|(_, to)| to.clone())
+}
+
+/// Whether the user asked to never treat this window title as media
+pub fn is_ignored(window_title: &str) -> bool {
+    RULES
+        .read()
+        .map(|rules| rules.ignore.iter().any(|re| re.is_match(window_title)))
+        .unwrap_or(false)
+}
+
+/// Serializes tests that install rules into the global state
+#[cfg(test)]
+static TEST_RULES_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Rules installed for the length of a test; the previous rules come back on drop
+///
+/// Other parser tests still read the global rules while one is installed, so
+/// test rules must only match inputs no other test uses.
+#[cfg(test)]
+pub(crate) struct ScopedRules {
+    previous: ParsingRules,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl ScopedRules {
+    pub(crate) fn install(rules: ParsingRules) -> Self {
+        // A failed test poisons the lock, but the rules are restored by then
+        let lock = TEST_RULES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = get_rules();
+        set_rules(rules).unwrap();
+        ScopedRules {
+            previous,
+            _lock: lock,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Drop for ScopedRules {
+    fn drop(&mut self) {
+        let _ = set_rules(std::mem::take(&mut self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let json = r#"{
+            "player_suffixes": [" - Kodi"],
+            "patterns": ["^(?P<title>.+?) #(?P<episode>\\d+)"],
+            "title_rewrites": { "Oshi no Ko": "[Oshi no Ko]" },
+            "ignore_patterns": ["trailer"]
+        }"#;
+        let rules = parse_rules(json).unwrap();
+        assert_eq!(rules.count(), 4);
+
+        let compiled = CompiledParsingRules::new(rules).unwrap();
+        assert!(compiled.ignore[0].is_match("Frieren Official TRAILER"));
+        assert_eq!(
+            compiled.rewrites,
+            vec![("oshi no ko".to_string(), "[Oshi no Ko]".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_reported() {
+        let err = parse_rules(r#"{"patterns": ["("]}"#).unwrap_err();
+        assert!(err.starts_with("Pattern #1 is invalid"), "{}", err);
+
+        let err = parse_rules(r#"{"patterns": ["(?P<titel>.+) (?P<episode>\\d+)"]}"#).unwrap_err();
+        assert!(err.contains("unknown group 'titel'"), "{}", err);
+
+        let err = parse_rules(r#"{"patterns": ["^\\d+$"]}"#).unwrap_err();
+        assert!(
+            err.contains("needs a 'title' or 'episode' group"),
+            "{}",
+            err
+        );
+
+        let err = parse_rules(r#"{"ignore_patterns": ["[a-"]}"#).unwrap_err();
+        assert!(err.starts_with("Ignore pattern #1"), "{}", err);
+
+        assert!(parse_rules(r#"{"patterns": 5}"#).is_err());
+    }
+}
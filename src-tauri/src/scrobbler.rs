@@ -0,0 +1,764 @@
+//! Scrobbler Module
+//!
+//! PURPOSE: Decide when a detected episode has actually been watched and push
+//! the progress to AniList / MyAnimeList without the frontend's involvement
+//!
+//! APPROACH: A small state machine follows the current (series, episode)
+//! - Watch time only accumulates between consecutive "playing" samples, so
+//!   pauses and long gaps (sleep, app hidden) don't count
+//! - Sources that report position/duration complete at `completion_ratio`,
+//!   provided a share of the episode was actually watched (not just seeked)
+//! - Title-only sources complete after `min_watch_secs` of watch time
+//! - Rewatches (episode <= known progress, from the user's cached AniList
+//!   list or this session) never lower list progress, and `sync` re-reads
+//!   each remote entry so a stale local view can't either
+//! - Jumping ahead records how many episodes were skipped
+//!
+//! Settings (including the tokens) are kept in `scrobbler.json`, readable
+//! only by the current user.
+
+use crate::anilist::{AniListError, Anime};
+use crate::media_player::{PlaybackInfo, PlaybackState};
+use crate::title_parser::ParsedTitle;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Samples further apart than this are treated as a gap, not watch time
+pub const MAX_SAMPLE_GAP: Duration = Duration::from_secs(60);
+
+/// How long an episode may disappear (player closed, tab switched) and still resume
+const IDLE_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// With position data, at least this share of the episode must be watched
+/// in-session (resuming halfway through still counts, seeking to the end doesn't)
+const MIN_WATCHED_SHARE: f64 = 0.25;
+
+/// User-tunable scrobbling settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrobbleConfig {
+    pub enabled: bool,
+    /// Fraction of the episode (0.0 - 1.0) after which it counts as watched
+    pub completion_ratio: f64,
+    /// Watch time after which an episode counts when no duration is known
+    pub min_watch_secs: u64,
+    /// Tokens used to push updates (set by the frontend after login);
+    /// the AniList login stored by `anilist_auth` takes precedence
+    pub anilist_token: Option<String>,
+    pub mal_token: Option<String>,
+}
+
+impl Default for ScrobbleConfig {
+    fn default() -> Self {
+        ScrobbleConfig {
+            enabled: true,
+            completion_ratio: 0.8,
+            min_watch_secs: 15 * 60,
+            anilist_token: None,
+            mal_token: None,
+        }
+    }
+}
+
+/// Identifies a series across services
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SeriesKey {
+    pub anilist_id: Option<i32>,
+    pub mal_id: Option<i64>,
+    /// Parsed title, used when no service match exists
+    pub title: String,
+}
+
+/// One detection sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub series: SeriesKey,
+    /// Last episode in the file (the one progress is set to)
+    pub episode: i32,
+    /// First episode in the file; differs from `episode` for "E01+E02"-style files
+    pub first_episode: i32,
+    pub total_episodes: Option<i32>,
+    pub position_secs: Option<f64>,
+    pub duration_secs: Option<f64>,
+    pub playing: bool,
+    /// Progress already on the user's list, if known
+    pub list_progress: Option<i32>,
+}
+
+impl Observation {
+    /// Build a sample from a detection result
+    ///
+    /// None if no episode (or one below 1) was parsed or the file is a
+    /// creditless OP/ED or preview.
+    pub fn from_detection(
+        parsed: &ParsedTitle,
+        anime: Option<&Anime>,
+        playback: Option<&PlaybackInfo>,
+    ) -> Option<Self> {
+        if !parsed.kind.is_countable() {
+            return None;
+        }
+        let episode = parsed.episode.filter(|&episode| episode > 0)?;
+        let title = anime
+            .and_then(|a| a.title.romaji.clone())
+            .or_else(|| parsed.title.clone())?;
+
+        Some(Observation {
+            series: SeriesKey {
+                anilist_id: anime.map(|a| a.id),
+                mal_id: anime.and_then(|a| a.id_mal).map(|id| id as i64),
+                title,
+            },
+            episode,
+            first_episode: parsed.episode_range.map_or(episode, |r| r.start),
+            total_episodes: anime.and_then(|a| a.episodes),
+            position_secs: playback.and_then(|p| p.position_secs),
+            duration_secs: playback.and_then(|p| p.duration_secs),
+            playing: playback.is_none_or(|p| p.state == PlaybackState::Playing),
+            list_progress: None,
+        })
+    }
+
+    /// Set the progress already on the user's list
+    pub fn with_list_progress(mut self, list_progress: Option<i32>) -> Self {
+        self.list_progress = list_progress;
+        self
+    }
+}
+
+/// What to do with the list entry once an episode is watched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScrobbleAction {
+    /// Set progress (status is an AniList MediaListStatus)
+    Update {
+        progress: i32,
+        status: String,
+        skipped_episodes: i32,
+    },
+    /// Episode was already counted; progress stays where it is
+    Rewatch { progress: i32 },
+}
+
+/// State transitions reported to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScrobbleEvent {
+    Started {
+        series: SeriesKey,
+        episode: i32,
+    },
+    Completed {
+        series: SeriesKey,
+        episode: i32,
+        action: ScrobbleAction,
+    },
+    /// Playback moved on before the episode counted as watched
+    Abandoned {
+        series: SeriesKey,
+        episode: i32,
+        watched_secs: u64,
+    },
+}
+
+impl ScrobbleEvent {
+    /// Tauri event name for this transition
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            ScrobbleEvent::Started { .. } => "scrobble-started",
+            ScrobbleEvent::Completed { .. } => "scrobble-completed",
+            ScrobbleEvent::Abandoned { .. } => "scrobble-abandoned",
+        }
+    }
+}
+
+/// The episode currently being followed
+#[derive(Debug)]
+struct Session {
+    series: SeriesKey,
+    episode: i32,
+    first_episode: i32,
+    total_episodes: Option<i32>,
+    watched: Duration,
+    last_seen: Instant,
+    playing: bool,
+    position_secs: Option<f64>,
+    duration_secs: Option<f64>,
+    completed: bool,
+}
+
+/// Watch-completion state machine
+pub struct Tracker {
+    config: ScrobbleConfig,
+    current: Option<Session>,
+    /// Highest episode known to be on each list
+    progress: HashMap<SeriesKey, i32>,
+}
+
+impl Tracker {
+    pub fn new(config: ScrobbleConfig) -> Self {
+        Tracker {
+            config,
+            current: None,
+            progress: HashMap::new(),
+        }
+    }
+
+    /// Feed one detection sample (None = nothing playing) taken at `now`
+    pub fn observe(
+        &mut self,
+        observation: Option<Observation>,
+        now: Instant,
+    ) -> Vec<ScrobbleEvent> {
+        let mut events = Vec::new();
+
+        let observation = match observation {
+            Some(o) => o,
+            None => {
+                let expired = match self.current.as_mut() {
+                    Some(session) => {
+                        session.playing = false;
+                        now.duration_since(session.last_seen) > IDLE_GRACE
+                    }
+                    None => false,
+                };
+                if expired {
+                    events.extend(self.finish());
+                }
+                return events;
+            }
+        };
+
+        if let Some(known) = observation.list_progress {
+            let entry = self
+                .progress
+                .entry(observation.series.clone())
+                .or_insert(known);
+            *entry = (*entry).max(known);
+        }
+
+        let is_same = self.current.as_ref().is_some_and(|s| {
+            s.series == observation.series
+                && s.episode == observation.episode
+                && now.duration_since(s.last_seen) <= IDLE_GRACE
+        });
+
+        if is_same {
+            if let Some(session) = self.current.as_mut() {
+                let gap = now.duration_since(session.last_seen);
+                if session.playing && observation.playing && gap <= MAX_SAMPLE_GAP {
+                    session.watched += gap;
+                }
+                session.last_seen = now;
+                session.playing = observation.playing;
+                session.position_secs = observation.position_secs.or(session.position_secs);
+                session.duration_secs = observation.duration_secs.or(session.duration_secs);
+                session.total_episodes = observation.total_episodes.or(session.total_episodes);
+            }
+        } else {
+            events.extend(self.finish());
+            events.push(ScrobbleEvent::Started {
+                series: observation.series.clone(),
+                episode: observation.episode,
+            });
+            self.current = Some(Session {
+                series: observation.series,
+                episode: observation.episode,
+                first_episode: observation.first_episode,
+                total_episodes: observation.total_episodes,
+                watched: Duration::ZERO,
+                last_seen: now,
+                playing: observation.playing,
+                position_secs: observation.position_secs,
+                duration_secs: observation.duration_secs,
+                completed: false,
+            });
+        }
+
+        let ready = self
+            .current
+            .as_ref()
+            .is_some_and(|s| !s.completed && self.is_watched(s));
+        if ready {
+            if let Some(event) = self.complete_current() {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    fn is_watched(&self, session: &Session) -> bool {
+        match (session.position_secs, session.duration_secs) {
+            (Some(position), Some(duration)) if duration > 0.0 => {
+                position / duration >= self.config.completion_ratio
+                    && session.watched.as_secs_f64() >= duration * MIN_WATCHED_SHARE
+            }
+            _ => session.watched.as_secs() >= self.config.min_watch_secs,
+        }
+    }
+
+    /// Mark the current episode watched and decide the list update
+    fn complete_current(&mut self) -> Option<ScrobbleEvent> {
+        let session = self.current.as_mut()?;
+        session.completed = true;
+
+        let known = self.progress.get(&session.series).copied();
+        let action = match known {
+            Some(progress) if session.episode <= progress => ScrobbleAction::Rewatch { progress },
+            _ => {
+                let skipped = known.map_or(0, |k| (session.first_episode - k - 1).max(0));
+                let status = if session.total_episodes == Some(session.episode) {
+                    "COMPLETED"
+                } else {
+                    "CURRENT"
+                };
+                self.progress
+                    .insert(session.series.clone(), session.episode);
+                ScrobbleAction::Update {
+                    progress: session.episode,
+                    status: status.to_string(),
+                    skipped_episodes: skipped,
+                }
+            }
+        };
+
+        Some(ScrobbleEvent::Completed {
+            series: session.series.clone(),
+            episode: session.episode,
+            action,
+        })
+    }
+
+    /// Stop following the current episode
+    fn finish(&mut self) -> Option<ScrobbleEvent> {
+        let session = self.current.take()?;
+        if session.completed {
+            return None;
+        }
+
+        Some(ScrobbleEvent::Abandoned {
+            series: session.series,
+            episode: session.episode,
+            watched_secs: session.watched.as_secs(),
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref TRACKER: Mutex<Tracker> = Mutex::new(Tracker::new(load_config_file()));
+}
+
+fn config_path() -> PathBuf {
+    crate::app_data_dir().join("scrobbler.json")
+}
+
+fn load_config_file() -> ScrobbleConfig {
+    let json = match std::fs::read_to_string(config_path()) {
+        Ok(json) => json,
+        Err(_) => return ScrobbleConfig::default(),
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("[Scrobbler] Ignoring unreadable settings file: {}", e);
+        ScrobbleConfig::default()
+    })
+}
+
+fn save_config_file(config: &ScrobbleConfig) -> Result<(), String> {
+    let path = config_path();
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to encode scrobbler settings: {}", e))?;
+    // Holds the AniList/MAL tokens
+    crate::anilist_auth::write_private(&path, json.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+pub fn get_config() -> ScrobbleConfig {
+    TRACKER.lock().map(|t| t.config.clone()).unwrap_or_default()
+}
+
+/// Replace and save the scrobbler settings
+pub fn set_config(config: ScrobbleConfig) -> Result<(), String> {
+    let mut tracker = TRACKER.lock().map_err(|_| "Tracker lock error")?;
+    save_config_file(&config)?;
+    tracker.config = config;
+    Ok(())
+}
+
+/// Feed a detection sample into the global tracker
+pub fn observe(observation: Option<Observation>) -> Vec<ScrobbleEvent> {
+    match TRACKER.lock() {
+        Ok(mut tracker) if tracker.config.enabled => tracker.observe(observation, Instant::now()),
+        _ => Vec::new(),
+    }
+}
+
+/// Outcome of pushing a completed episode to one service
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncResult {
+    pub service: String,
+    pub series: SeriesKey,
+    pub success: bool,
+    pub message: String,
+    /// The service refused the token; the user has to log in again
+    pub reauth_required: bool,
+}
+
+/// Whether setting `progress` moves the remote entry forward
+fn raises_progress(progress: i32, remote: Option<i32>) -> bool {
+    progress > 0 && remote.is_none_or(|remote| progress > remote)
+}
+
+/// Push a completed episode to every service we have a token and id for
+///
+/// Each service's entry is read first and left alone when it is already at or
+/// past `progress`.
+pub async fn sync(series: &SeriesKey, action: &ScrobbleAction) -> Vec<SyncResult> {
+    let (progress, status) = match action {
+        ScrobbleAction::Update {
+            progress, status, ..
+        } => (*progress, status.as_str()),
+        ScrobbleAction::Rewatch { .. } => return Vec::new(),
+    };
+
+    let config = get_config();
+    let mut results = Vec::new();
+
+    // The backend's stored login first, the token the frontend set otherwise
+    let anilist_token = crate::anilist_auth::access_token().or(config.anilist_token);
+    if let (Some(token), Some(media_id)) = (anilist_token.as_deref(), series.anilist_id) {
+        let outcome = sync_anilist(token, media_id, progress, status).await;
+        results.push(SyncResult {
+            service: "anilist".to_string(),
+            series: series.clone(),
+            success: outcome.is_ok(),
+            reauth_required: matches!(outcome, Err(AniListError::InvalidToken(_))),
+            message: match outcome {
+                Ok(message) => message,
+                Err(e) => e.to_string(),
+            },
+        });
+    }
+
+    if let (Some(token), Some(anime_id)) = (config.mal_token.as_deref(), series.mal_id) {
+        let outcome = sync_mal(token, anime_id, progress, status).await;
+        results.push(SyncResult {
+            service: "myanimelist".to_string(),
+            series: series.clone(),
+            success: outcome.is_ok(),
+            reauth_required: false,
+            message: match outcome {
+                Ok(message) => message,
+                Err(e) => e,
+            },
+        });
+    }
+
+    results
+}
+
+async fn sync_anilist(
+    token: &str,
+    media_id: i32,
+    progress: i32,
+    status: &str,
+) -> Result<String, AniListError> {
+    let remote = crate::anilist::get_media_list_entry(token, media_id)
+        .await?
+        .map(|entry| entry.progress);
+    if !raises_progress(progress, remote) {
+        return Ok(format!(
+            "List already at {}, left unchanged",
+            remote.unwrap_or(0)
+        ));
+    }
+    let entry =
+        crate::anilist::update_media_progress(token, media_id, progress, Some(status)).await?;
+    Ok(format!("Progress set to {}", entry.progress))
+}
+
+async fn sync_mal(
+    token: &str,
+    anime_id: i64,
+    progress: i32,
+    status: &str,
+) -> Result<String, String> {
+    let remote = crate::myanimelist::get_anime_list_status(token, anime_id)
+        .await?
+        .and_then(|entry| entry.num_episodes_watched);
+    if !raises_progress(progress, remote) {
+        return Ok(format!(
+            "List already at {}, left unchanged",
+            remote.unwrap_or(0)
+        ));
+    }
+    let mal_status = if status == "COMPLETED" {
+        "completed"
+    } else {
+        "watching"
+    };
+    let entry =
+        crate::myanimelist::update_anime_progress(token, anime_id, progress, Some(mal_status))
+            .await?;
+    Ok(format!(
+        "Progress set to {}",
+        entry.num_episodes_watched.unwrap_or(progress)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series() -> SeriesKey {
+        SeriesKey {
+            anilist_id: Some(154587),
+            mal_id: Some(52991),
+            title: "Sousou no Frieren".to_string(),
+        }
+    }
+
+    fn sample(episode: i32, position: Option<f64>, playing: bool) -> Observation {
+        Observation {
+            series: series(),
+            episode,
+            first_episode: episode,
+            total_episodes: Some(28),
+            position_secs: position,
+            duration_secs: position.map(|_| 1440.0),
+            playing,
+            list_progress: None,
+        }
+    }
+
+    /// Feed samples every `step` seconds, returning all events
+    fn run(
+        tracker: &mut Tracker,
+        start: Instant,
+        samples: &[(u64, Option<Observation>)],
+    ) -> Vec<ScrobbleEvent> {
+        samples
+            .iter()
+            .flat_map(|(secs, obs)| {
+                tracker.observe(obs.clone(), start + Duration::from_secs(*secs))
+            })
+            .collect()
+    }
+
+    fn completed_actions(events: &[ScrobbleEvent]) -> Vec<ScrobbleAction> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                ScrobbleEvent::Completed { action, .. } => Some(action.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_title_only_completes_after_watch_time() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        // 15 minutes of 30s samples
+        let samples: Vec<_> = (0..=30)
+            .map(|i| (i * 30, Some(sample(5, None, true))))
+            .collect();
+        let events = run(&mut tracker, start, &samples);
+
+        assert!(matches!(
+            events[0],
+            ScrobbleEvent::Started { episode: 5, .. }
+        ));
+        assert_eq!(
+            completed_actions(&events),
+            vec![ScrobbleAction::Update {
+                progress: 5,
+                status: "CURRENT".to_string(),
+                skipped_episodes: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pause_does_not_count() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        // Paused for 20 minutes: no watch time accumulates
+        let samples: Vec<_> = (0..=40)
+            .map(|i| (i * 30, Some(sample(5, None, false))))
+            .collect();
+        let events = run(&mut tracker, start, &samples);
+        assert!(completed_actions(&events).is_empty());
+    }
+
+    #[test]
+    fn test_position_completion_requires_real_watching() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        // Seeking straight to 90% doesn't count
+        let events = run(
+            &mut tracker,
+            start,
+            &[
+                (0, Some(sample(3, Some(10.0), true))),
+                (5, Some(sample(3, Some(1300.0), true))),
+            ],
+        );
+        assert!(completed_actions(&events).is_empty());
+
+        // Resuming at 50% and watching to 80% does (7.5 minutes in-session)
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let samples: Vec<_> = (0..=16)
+            .map(|i| (i * 30, Some(sample(3, Some(720.0 + i as f64 * 30.0), true))))
+            .collect();
+        let events = run(&mut tracker, start, &samples);
+        assert_eq!(completed_actions(&events).len(), 1);
+    }
+
+    #[test]
+    fn test_rewatch_and_skip() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        let mut known = sample(4, None, true);
+        known.list_progress = Some(6);
+        let samples: Vec<_> = (0..=30).map(|i| (i * 30, Some(known.clone()))).collect();
+        let events = run(&mut tracker, start, &samples);
+        assert_eq!(
+            completed_actions(&events),
+            vec![ScrobbleAction::Rewatch { progress: 6 }]
+        );
+
+        // Jump from 6 to 9: two episodes skipped
+        let samples: Vec<_> = (0..=30)
+            .map(|i| (2000 + i * 30, Some(sample(9, None, true))))
+            .collect();
+        let events = run(&mut tracker, start, &samples);
+        assert_eq!(
+            completed_actions(&events),
+            vec![ScrobbleAction::Update {
+                progress: 9,
+                status: "CURRENT".to_string(),
+                skipped_episodes: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_list_progress_stops_downgrade_after_restart() {
+        // A fresh tracker knows nothing from this session; only the list says 10
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        let mut rewatch = sample(3, None, true);
+        rewatch.list_progress = Some(10);
+        let samples: Vec<_> = (0..=30).map(|i| (i * 30, Some(rewatch.clone()))).collect();
+        let events = run(&mut tracker, start, &samples);
+        assert_eq!(
+            completed_actions(&events),
+            vec![ScrobbleAction::Rewatch { progress: 10 }]
+        );
+    }
+
+    #[test]
+    fn test_multi_episode_file_is_not_a_skip() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        let mut double = sample(4, None, true);
+        double.first_episode = 3;
+        double.list_progress = Some(2);
+        let samples: Vec<_> = (0..=30).map(|i| (i * 30, Some(double.clone()))).collect();
+        let events = run(&mut tracker, start, &samples);
+        assert_eq!(
+            completed_actions(&events),
+            vec![ScrobbleAction::Update {
+                progress: 4,
+                status: "CURRENT".to_string(),
+                skipped_episodes: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_switching_episode_abandons_previous() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        let events = run(
+            &mut tracker,
+            start,
+            &[
+                (0, Some(sample(1, None, true))),
+                (30, Some(sample(1, None, true))),
+                (60, Some(sample(2, None, true))),
+            ],
+        );
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[1],
+            ScrobbleEvent::Abandoned {
+                episode: 1,
+                watched_secs: 30,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[2],
+            ScrobbleEvent::Started { episode: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_brief_disappearance_resumes() {
+        let mut tracker = Tracker::new(ScrobbleConfig::default());
+        let start = Instant::now();
+
+        let events = run(
+            &mut tracker,
+            start,
+            &[
+                (0, Some(sample(1, None, true))),
+                (30, None),
+                (90, Some(sample(1, None, true))),
+            ],
+        );
+        assert_eq!(events.len(), 1, "no restart within the grace period");
+
+        let events = run(&mut tracker, start, &[(1000, None)]);
+        assert!(matches!(
+            events[0],
+            ScrobbleEvent::Abandoned { episode: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_sync_never_lowers_remote_progress() {
+        assert!(raises_progress(4, None));
+        assert!(raises_progress(4, Some(3)));
+        assert!(!raises_progress(3, Some(10)), "rewatch after a restart");
+        assert!(!raises_progress(10, Some(10)));
+        assert!(!raises_progress(0, None));
+    }
+
+    #[test]
+    fn test_non_positive_episode_is_not_observed() {
+        let parsed = |episode| ParsedTitle {
+            title: Some("Frieren".to_string()),
+            episode: Some(episode),
+            ..Default::default()
+        };
+        assert!(Observation::from_detection(&parsed(-19), None, None).is_none());
+        assert!(Observation::from_detection(&parsed(0), None, None).is_none());
+        assert_eq!(
+            Observation::from_detection(&parsed(5), None, None).map(|o| o.episode),
+            Some(5)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Streaming Site Title Grammars
+//!
+//! PURPOSE: Read the show out of browser tabs on streaming sites, where the
+//! window title is site boilerplate wrapped around it
+//! ("Watch Frieren Episode 5 English Sub at HiAnime - Google Chrome – Suraj")
+//!
+//! APPROACH: Strip the browser name (and the profile name some browsers put
+//! after it), then find the grammar whose marker matches the tab title.
+//! Its patterns use the named groups `title`, `season` and `episode`; when
+//! none of them matches, the boilerplate is removed and the rest goes to the
+//! generic parser.
+
+use regex::Regex;
+
+/// One streaming site's tab title format
+struct SiteGrammar {
+    /// Site id reported in `ParsedTitle::site`
+    name: &'static str,
+    /// Recognizes the site in a tab title
+    marker: Regex,
+    /// Structured patterns with named groups `title`, `season`, `episode`
+    patterns: Vec<Regex>,
+    /// Site boilerplate removed (in order) when no pattern matches
+    boilerplate: Vec<Regex>,
+}
+
+/// A tab title read with a site grammar
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteTitle {
+    /// Grammar name, e.g. "crunchyroll"
+    pub site: &'static str,
+    /// Tab title without browser suffix and site boilerplate
+    pub text: String,
+    /// Fields read by a structured pattern (all None when none matched)
+    pub title: Option<String>,
+    pub season: Option<i32>,
+    pub episode: Option<i32>,
+}
+
+impl SiteTitle {
+    /// Whether one of the site's structured patterns matched
+    pub fn is_structured(&self) -> bool {
+        self.title.is_some()
+    }
+}
+
+/// `Watch <title> [Season N] Episode N ...`, shared by most anime sites
+const EPISODE_PATTERN: &str = r"(?i)^(?:Watch\s+)?(?P<title>.+?)(?:\s+Season\s+(?P<season>\d{1,2}))?\s+Episode\s+(?P<episode>\d{1,4})\b";
+
+/// "English Sub", "Dubbed", "Eng Sub/Dub" and everything after it
+const SUB_DUB_BOILERPLATE: &str = r"(?i)\s+(?:English\s+|Eng\s+)?(?:Sub(?:bed)?|Dub(?:bed)?)\b.*$";
+
+fn grammar(
+    name: &'static str,
+    marker: &str,
+    patterns: &[&str],
+    boilerplate: &[&str],
+) -> SiteGrammar {
+    let compile = |p: &&str| Regex::new(p).expect("built-in site grammar must compile");
+    SiteGrammar {
+        name,
+        marker: compile(&marker),
+        patterns: patterns.iter().map(compile).collect(),
+        boilerplate: boilerplate.iter().map(compile).collect(),
+    }
+}
+
+/// The built-in grammar table, checked in order
+fn default_grammars() -> Vec<SiteGrammar> {
+    vec![
+        grammar(
+            "hianime",
+            r"(?i)\b(?:HiAnime|Aniwatch|Zoro)(?:\.\w+)?\b",
+            &[EPISODE_PATTERN],
+            &[
+                r"(?i)^Watch\s+",
+                SUB_DUB_BOILERPLATE,
+                r"(?i)\s+(?:online\s+)?(?:free\s+)?(?:on|at)\s+(?:HiAnime|Aniwatch|Zoro)\S*.*$",
+                r"(?i)\s*[-|]\s*(?:HiAnime|Aniwatch|Zoro)\S*\s*$",
+            ],
+        ),
+        grammar(
+            "crunchyroll",
+            r"(?i)\bCrunchyroll\b",
+            &[
+                r"(?i)^Crunchyroll\s*-\s*Watch\s+(?P<title>.+?)(?:\s+Season\s+(?P<season>\d{1,2}))?\s+Episode\s+(?P<episode>\d{1,4})\b",
+                r"(?i)^(?:Watch\s+)?(?P<title>.+?)(?:\s+Season\s+(?P<season>\d{1,2}))?\s+(?:Episode\s+|E)(?P<episode>\d{1,4})\b.*?-\s*(?:Watch\s+on\s+)?Crunchyroll$",
+            ],
+            &[
+                r"(?i)^Crunchyroll\s*-\s*",
+                r"(?i)^Watch\s+",
+                r"(?i)\s*-\s*(?:Watch\s+on\s+)?Crunchyroll\s*$",
+            ],
+        ),
+        grammar(
+            "hidive",
+            r"(?i)\bHIDIVE\b",
+            &[
+                r"(?i)^(?:Watch\s+)?(?P<title>.+?)(?:\s+Season\s+(?P<season>\d{1,2}))?\s+(?:Episode|Ep\.?)\s*(?P<episode>\d{1,4})\b.*?[|-]\s*HIDIVE$",
+            ],
+            &[
+                r"(?i)^Watch\s+",
+                r"(?i)\s*[|-]\s*(?:Stream\s+on\s+)?HIDIVE\s*$",
+            ],
+        ),
+        grammar(
+            "netflix",
+            r"(?i)\bNetflix\b",
+            &[
+                r"(?i)^(?:Watch\s+)?(?P<title>.+?)\s+S(?P<season>\d{1,2}):\s*E(?P<episode>\d{1,4})\b.*?\|\s*Netflix",
+                r"(?i)^(?:Watch\s+)?(?P<title>.+?)\s*\|\s*Netflix(?:\s+Official\s+Site)?$",
+            ],
+            &[r"(?i)^Watch\s+", r"(?i)\s*\|?\s*Netflix.*$"],
+        ),
+        grammar(
+            "prime-video",
+            r"(?i)\bPrime\s+Video\b",
+            &[
+                r"(?i)^Prime\s+Video:\s*(?:Watch\s+)?(?P<title>.+?)(?:\s*[-:]\s*Season\s+(?P<season>\d{1,2}))?$",
+            ],
+            &[
+                r"(?i)^Prime\s+Video:\s*",
+                r"(?i)\s*[-|]\s*Prime\s+Video\s*$",
+            ],
+        ),
+        grammar(
+            "gogoanime",
+            r"(?i)\b(?:gogoanime|anitaku)\b",
+            &[EPISODE_PATTERN],
+            &[
+                r"(?i)^Watch\s+",
+                SUB_DUB_BOILERPLATE,
+                r"(?i)\s+(?:at|on|-)\s+(?:gogoanime|anitaku)\S*.*$",
+            ],
+        ),
+        grammar(
+            "9anime",
+            r"(?i)\b(?:9anime|aniwave)\b",
+            &[EPISODE_PATTERN],
+            &[
+                r"(?i)^Watch\s+",
+                SUB_DUB_BOILERPLATE,
+                r"(?i)\s+(?:online\s+)?(?:free\s+)?(?:at|on|-)\s+(?:9anime|aniwave)\S*.*$",
+            ],
+        ),
+        grammar(
+            "animepahe",
+            r"(?i)::\s*animepahe",
+            &[r"(?i)^(?P<title>.+?)\s+Ep\.?\s*(?P<episode>\d{1,4})\b"],
+            &[r"(?i)\s*::\s*animepahe.*$"],
+        ),
+        grammar(
+            "youtube",
+            r"(?i)\s-\s*YouTube(?:\s+Music)?$",
+            &[],
+            &[r"^\(\d+\)\s*", r"(?i)\s*-\s*YouTube(?:\s+Music)?$"],
+        ),
+    ]
+}
+
+lazy_static::lazy_static! {
+    static ref GRAMMARS: Vec<SiteGrammar> = default_grammars();
+
+    /// " - Google Chrome", " — Mozilla Firefox", "- Microsoft​ Edge", optionally
+    /// followed by the profile name ("– Suraj")
+    static ref BROWSER_SUFFIX: Regex = Regex::new(
+        r"(?i)\s+[-–—|]\s+(?:Google\s+Chrome|Chromium|Chrome|Mozilla\s+Firefox|Firefox(?:\s+Developer\s+Edition|\s+Nightly)?|Microsoft\W*Edge|Edge|Brave|Opera(?:\s+GX)?|Vivaldi|Zen\s+Browser|LibreWolf|Floorp|Waterfox|Thorium|Arc|Safari)(?:\s+Private\s+Browsing)?(?:\s+[-–—]\s+[^-–—]{1,40})?\s*$"
+    )
+    .unwrap();
+
+    /// Edge's "and 3 more pages" for tab groups
+    static ref MORE_PAGES: Regex = Regex::new(r"(?i)\s+and\s+\d+\s+more\s+pages?\s*$").unwrap();
+}
+
+/// Remove a trailing browser name, profile name and tab-group note
+pub fn strip_browser_suffix(title: &str) -> String {
+    let stripped = BROWSER_SUFFIX.replace(title, "");
+    MORE_PAGES.replace(&stripped, "").trim().to_string()
+}
+
+/// Read a (browser-stripped) tab title with the first grammar whose marker matches
+pub fn match_site(title: &str) -> Option<SiteTitle> {
+    let grammar = GRAMMARS.iter().find(|g| g.marker.is_match(title))?;
+
+    let text = grammar
+        .boilerplate
+        .iter()
+        .fold(title.to_string(), |text, re| {
+            re.replace(&text, "").to_string()
+        })
+        .trim()
+        .to_string();
+
+    let mut site = SiteTitle {
+        site: grammar.name,
+        text,
+        title: None,
+        season: None,
+        episode: None,
+    };
+
+    let caps = grammar.patterns.iter().find_map(|re| re.captures(title));
+    if let Some(caps) = caps {
+        let number = |group: &str| caps.name(group).and_then(|m| m.as_str().parse().ok());
+        site.title = caps
+            .name("title")
+            .map(|m| m.as_str().trim().to_string())
+            .filter(|t| !t.is_empty());
+        site.season = number("season");
+        site.episode = number("episode");
+    }
+
+    Some(site)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_browser_suffix() {
+        assert_eq!(
+            strip_browser_suffix("Frieren Episode 5 at HiAnime - Google Chrome – Suraj"),
+            "Frieren Episode 5 at HiAnime"
+        );
+        assert_eq!(
+            strip_browser_suffix("Frieren | Netflix — Mozilla Firefox"),
+            "Frieren | Netflix"
+        );
+        assert_eq!(
+            strip_browser_suffix("Frieren | Netflix and 2 more pages - Microsoft\u{200b} Edge"),
+            "Frieren | Netflix"
+        );
+        // File names keep their dashes
+        assert_eq!(strip_browser_suffix("Frieren - 05.mkv"), "Frieren - 05.mkv");
+    }
+
+    #[test]
+    fn test_unstructured_site_title() {
+        let site = match_site("(3) Frieren Episode 5 [English Sub] - YouTube").unwrap();
+        assert_eq!(site.site, "youtube");
+        assert_eq!(site.text, "Frieren Episode 5 [English Sub]");
+        assert!(!site.is_structured());
+
+        assert!(match_site("Frieren - 05.mkv").is_none());
+    }
+}
@@ -4,6 +4,8 @@ use warp::Filter;
 
 lazy_static::lazy_static! {
     pub static ref SERVER_PORT: Mutex<u16> = Mutex::new(0);
+    /// `URI="..."` attributes in HLS playlist tags
+    static ref URI_ATTR_RE: regex::Regex = regex::Regex::new(r#"URI="([^"]+)""#).unwrap();
 }
 
 pub async fn start_server() -> u16 {
@@ -208,8 +210,6 @@ async fn handle_proxy_request(
                             };
                             println!("[StreamServer] Using host_str for rewrite: {}", host_str);
 
-                            let uri_re = regex::Regex::new(r#"URI="([^"]+)""#).unwrap();
-
                             for line in text.lines() {
                                 let trimmed = line.trim();
                                 if trimmed.is_empty() {
@@ -217,7 +217,7 @@ async fn handle_proxy_request(
                                 }
                                 if trimmed.starts_with('#') {
                                     if trimmed.contains("URI=\"") {
-                                        let rewritten_line = uri_re.replace_all(
+                                        let rewritten_line = URI_ATTR_RE.replace_all(
                                             trimmed,
                                             |caps: &regex::Captures| {
                                                 let original_uri = &caps[1];
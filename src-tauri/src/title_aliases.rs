@@ -0,0 +1,383 @@
+//! Title Aliases Module
+//!
+//! PURPOSE: Remember the user's corrections so a title that matched the wrong
+//! show is matched right from then on
+//!
+//! APPROACH: An alias maps a parsed title (normalized like `title_matcher`
+//! compares titles), optionally narrowed to a release group and/or the folder
+//! the file sits in, to an AniList or MAL ID and an episode offset. Aliases
+//! live in `title_aliases.sqlite3` next to the AniList cache, but in their own
+//! file: the cache can be thrown away, corrections can't. Detection checks
+//! them before any search; the most specific alias wins.
+
+use crate::title_matcher;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A learned title → entry mapping
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TitleAlias {
+    /// Row ID (assigned on insert, ignored on import)
+    #[serde(default)]
+    pub id: i64,
+    /// Parsed title the alias applies to
+    pub title: String,
+    /// Only for releases by this group
+    #[serde(default)]
+    pub release_group: Option<String>,
+    /// Only for files in this folder (folder name, not a full path)
+    #[serde(default)]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub anilist_id: Option<i32>,
+    #[serde(default)]
+    pub mal_id: Option<i32>,
+    /// Subtracted from parsed episode numbers (24 turns episode 25 into 1)
+    #[serde(default)]
+    pub episode_offset: i32,
+}
+
+impl TitleAlias {
+    /// A parsed episode with the offset applied
+    ///
+    /// None when there is no offset, or the episode is at or below it (the
+    /// file is numbered within its season already).
+    pub fn map_episode(&self, episode: i32) -> Option<i32> {
+        (self.episode_offset != 0 && episode > self.episode_offset)
+            .then(|| episode - self.episode_offset)
+    }
+}
+
+fn current_ts_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs() as i64
+}
+
+fn aliases_db_path() -> PathBuf {
+    crate::app_data_dir().join("title_aliases.sqlite3")
+}
+
+fn open_aliases_db() -> Result<Connection, String> {
+    let conn = Connection::open(aliases_db_path())
+        .map_err(|e| format!("Failed to open aliases DB: {}", e))?;
+    init_table(&conn)?;
+    Ok(conn)
+}
+
+/// Optional columns hold '' rather than NULL so the UNIQUE key covers them
+fn init_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS title_aliases (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            release_group TEXT NOT NULL DEFAULT '',
+            folder TEXT NOT NULL DEFAULT '',
+            anilist_id INTEGER,
+            mal_id INTEGER,
+            episode_offset INTEGER NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL,
+            UNIQUE (title, release_group, folder)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to initialize aliases table: {}", e))?;
+    Ok(())
+}
+
+/// Release groups compare case-insensitively
+fn normalize_group(group: Option<&str>) -> String {
+    group.map(|g| g.trim().to_lowercase()).unwrap_or_default()
+}
+
+/// Folders compare like titles ("Kaguya-sama S2" = "kaguya sama s2")
+fn normalize_folder(folder: Option<&str>) -> String {
+    folder.map(title_matcher::normalize).unwrap_or_default()
+}
+
+fn alias_from_row(row: &Row) -> rusqlite::Result<TitleAlias> {
+    let release_group: String = row.get(2)?;
+    let folder: String = row.get(3)?;
+    Ok(TitleAlias {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        release_group: Some(release_group).filter(|g| !g.is_empty()),
+        folder: Some(folder).filter(|f| !f.is_empty()),
+        anilist_id: row.get(4)?,
+        mal_id: row.get(5)?,
+        episode_offset: row.get(6)?,
+    })
+}
+
+const ALIAS_COLUMNS: &str = "id, title, release_group, folder, anilist_id, mal_id, episode_offset";
+
+/// Insert or replace the alias with the same title, group and folder
+fn upsert(conn: &Connection, alias: &TitleAlias) -> Result<TitleAlias, String> {
+    let title = title_matcher::normalize(&alias.title);
+    if title.is_empty() {
+        return Err("Alias title is empty".to_string());
+    }
+    if alias.anilist_id.is_none() && alias.mal_id.is_none() {
+        return Err(format!(
+            "Alias '{}' needs an AniList or MAL ID",
+            alias.title
+        ));
+    }
+    let group = normalize_group(alias.release_group.as_deref());
+    let folder = normalize_folder(alias.folder.as_deref());
+
+    conn.execute(
+        "INSERT INTO title_aliases
+            (title, release_group, folder, anilist_id, mal_id, episode_offset, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(title, release_group, folder) DO UPDATE SET
+           anilist_id = excluded.anilist_id,
+           mal_id = excluded.mal_id,
+           episode_offset = excluded.episode_offset,
+           updated_at = excluded.updated_at",
+        params![
+            title,
+            group,
+            folder,
+            alias.anilist_id,
+            alias.mal_id,
+            alias.episode_offset,
+            current_ts_secs()
+        ],
+    )
+    .map_err(|e| format!("Failed to save alias: {}", e))?;
+
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM title_aliases
+             WHERE title = ?1 AND release_group = ?2 AND folder = ?3",
+            ALIAS_COLUMNS
+        ),
+        params![title, group, folder],
+        alias_from_row,
+    )
+    .map_err(|e| format!("Failed to read alias: {}", e))
+}
+
+fn list(conn: &Connection) -> Result<Vec<TitleAlias>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM title_aliases ORDER BY title, release_group, folder",
+            ALIAS_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to read aliases: {}", e))?;
+    let rows = stmt
+        .query_map([], alias_from_row)
+        .map_err(|e| format!("Failed to read aliases: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read aliases: {}", e))
+}
+
+/// Most specific alias for a title: group and folder, then either, then neither
+fn find(
+    conn: &Connection,
+    title: &str,
+    release_group: Option<&str>,
+    folder: Option<&str>,
+) -> Result<Option<TitleAlias>, String> {
+    let title = title_matcher::normalize(title);
+    if title.is_empty() {
+        return Ok(None);
+    }
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM title_aliases
+             WHERE title = ?1
+               AND release_group IN ('', ?2)
+               AND folder IN ('', ?3)
+             ORDER BY (release_group != '') + (folder != '') DESC, folder != '' DESC
+             LIMIT 1",
+            ALIAS_COLUMNS
+        ),
+        params![
+            title,
+            normalize_group(release_group),
+            normalize_folder(folder)
+        ],
+        alias_from_row,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to look up alias: {}", e))
+}
+
+/// Add an alias, replacing the one with the same title, group and folder
+///
+/// # Returns
+/// * The stored alias (normalized, with its ID)
+pub fn add_alias(alias: &TitleAlias) -> Result<TitleAlias, String> {
+    let conn = open_aliases_db()?;
+    let stored = upsert(&conn, alias)?;
+    println!(
+        "[Aliases] '{}' → AniList {:?} / MAL {:?} (offset {})",
+        stored.title, stored.anilist_id, stored.mal_id, stored.episode_offset
+    );
+    Ok(stored)
+}
+
+/// All aliases, sorted by title
+pub fn list_aliases() -> Result<Vec<TitleAlias>, String> {
+    list(&open_aliases_db()?)
+}
+
+/// Remove an alias by ID
+///
+/// # Returns
+/// * Whether an alias was removed
+pub fn remove_alias(id: i64) -> Result<bool, String> {
+    let conn = open_aliases_db()?;
+    let removed = conn
+        .execute("DELETE FROM title_aliases WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to remove alias: {}", e))?;
+    Ok(removed > 0)
+}
+
+/// Alias for a parsed title, if the user has taught one
+///
+/// # Arguments
+/// * `title` - Parsed title
+/// * `release_group` - Parsed release group, if any
+/// * `folder` - Name of the folder holding the file, if known
+pub fn find_alias(
+    title: &str,
+    release_group: Option<&str>,
+    folder: Option<&str>,
+) -> Result<Option<TitleAlias>, String> {
+    find(&open_aliases_db()?, title, release_group, folder)
+}
+
+/// All aliases as a JSON array, for backup or sharing
+pub fn export_aliases() -> Result<String, String> {
+    let aliases = list_aliases()?;
+    serde_json::to_string_pretty(&aliases).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Add every alias of an exported JSON array
+///
+/// Nothing is imported when any entry is invalid.
+///
+/// # Returns
+/// * Number of aliases imported
+pub fn import_aliases(json: &str) -> Result<usize, String> {
+    let aliases: Vec<TitleAlias> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid aliases JSON: {}", e))?;
+    let mut conn = open_aliases_db()?;
+    import_into(&mut conn, &aliases)
+}
+
+fn import_into(conn: &mut Connection, aliases: &[TitleAlias]) -> Result<usize, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start import: {}", e))?;
+    for alias in aliases {
+        upsert(&tx, alias)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to finish import: {}", e))?;
+    println!("[Aliases] Imported {} alias(es)", aliases.len());
+    Ok(aliases.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn alias(
+        title: &str,
+        group: Option<&str>,
+        folder: Option<&str>,
+        anilist_id: i32,
+    ) -> TitleAlias {
+        TitleAlias {
+            id: 0,
+            title: title.to_string(),
+            release_group: group.map(String::from),
+            folder: folder.map(String::from),
+            anilist_id: Some(anilist_id),
+            mal_id: None,
+            episode_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_most_specific_alias_wins() {
+        let conn = test_db();
+        upsert(&conn, &alias("Kaguya-sama", None, None, 1)).unwrap();
+        upsert(&conn, &alias("Kaguya-sama", Some("SubsPlease"), None, 2)).unwrap();
+        upsert(
+            &conn,
+            &alias("Kaguya-sama", None, Some("Kaguya-sama S2"), 3),
+        )
+        .unwrap();
+        upsert(
+            &conn,
+            &alias("Kaguya-sama", Some("SubsPlease"), Some("Kaguya-sama S2"), 4),
+        )
+        .unwrap();
+
+        let id = |group, folder| {
+            find(&conn, "kaguya sama", group, folder)
+                .unwrap()
+                .and_then(|a| a.anilist_id)
+        };
+        assert_eq!(id(None, None), Some(1));
+        assert_eq!(id(Some("subsplease"), None), Some(2));
+        assert_eq!(id(Some("Erai-raws"), Some("kaguya sama s2")), Some(3));
+        assert_eq!(id(Some("SubsPlease"), Some("Kaguya-sama S2")), Some(4));
+        assert_eq!(find(&conn, "Oshi no Ko", None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_upsert_replaces_and_validates() {
+        let conn = test_db();
+        let first = upsert(&conn, &alias("Oshi no Ko", None, None, 150672)).unwrap();
+        let mut fixed = alias("OSHI NO KO", None, None, 166531);
+        fixed.episode_offset = 11;
+        let second = upsert(&conn, &fixed).unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.title, "oshi no ko");
+        assert_eq!(list(&conn).unwrap(), vec![second]);
+
+        let mut no_id = alias("Oshi no Ko", None, None, 1);
+        no_id.anilist_id = None;
+        assert!(upsert(&conn, &no_id).is_err());
+        assert!(upsert(&conn, &alias(" - ", None, None, 1)).is_err());
+    }
+
+    #[test]
+    fn test_import_is_all_or_nothing() {
+        let mut conn = test_db();
+        let mut invalid = alias("Bad", None, None, 1);
+        invalid.anilist_id = None;
+        let batch = vec![alias("Frieren", None, None, 154587), invalid];
+
+        assert!(import_into(&mut conn, &batch).is_err());
+        assert!(list(&conn).unwrap().is_empty());
+        assert_eq!(import_into(&mut conn, &batch[..1]).unwrap(), 1);
+        assert_eq!(list(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_offset_never_goes_below_one() {
+        let mut offset = alias("One Punch Man", None, None, 97668);
+        offset.episode_offset = 12;
+        assert_eq!(offset.map_episode(15), Some(3));
+        assert_eq!(offset.map_episode(5), None, "already season-relative");
+        assert_eq!(offset.map_episode(12), None);
+        assert_eq!(alias("Frieren", None, None, 154587).map_episode(5), None);
+    }
+}
@@ -0,0 +1,338 @@
+//! Title Matcher Module
+//!
+//! PURPOSE: Pick the AniList entry a parsed title refers to from a page of
+//! search candidates, or none when no candidate is close enough
+//!
+//! APPROACH: Every candidate gets a score between 0 and 1. Most of it is the
+//! string similarity between the parsed title and the closest of the
+//! candidate's romaji, English and native titles and synonyms; the media
+//! format, release year and episode count adjust it. Factors the parse has
+//! nothing to say about (no year, no episode) are left out instead of
+//! counted as misses. AniList's own ranking breaks ties.
+
+use crate::anilist::{self, Anime};
+use crate::title_parser::{MediaKind, ParsedTitle};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use unicode_normalization::UnicodeNormalization;
+
+/// Candidates fetched per search
+const CANDIDATE_LIMIT: i32 = 10;
+
+/// Lowest score accepted as a match
+pub const MATCH_THRESHOLD: f64 = 0.6;
+
+const TITLE_WEIGHT: f64 = 0.7;
+const FORMAT_WEIGHT: f64 = 0.1;
+const YEAR_WEIGHT: f64 = 0.15;
+const EPISODE_WEIGHT: f64 = 0.1;
+
+/// Formats that hold numbered episodes of a series
+const SERIES_FORMATS: [&str; 3] = ["TV", "TV_SHORT", "ONA"];
+
+/// The chosen candidate and how well it matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleMatch {
+    pub anime: Anime,
+    /// 0.0 - 1.0, at least `MATCH_THRESHOLD`
+    pub score: f64,
+}
+
+/// AniList formats to search for a parsed kind (empty = any)
+///
+/// "Title - OVA 2" is looked up among OVAs so it maps to the OVA entry
+/// instead of episode 2 of the series.
+pub fn search_formats(kind: MediaKind) -> &'static [&'static str] {
+    match kind {
+        MediaKind::Ova => &["OVA"],
+        MediaKind::Ona => &["ONA"],
+        MediaKind::Movie => &["MOVIE"],
+        MediaKind::Special => &["SPECIAL", "OVA"],
+        _ => &[],
+    }
+}
+
+/// Fetch the search candidates for a title, among the formats of its kind
+pub async fn search_candidates(title: &str, kind: MediaKind) -> Result<Vec<Anime>, String> {
+    anilist::search_anime_with_formats(title, CANDIDATE_LIMIT, search_formats(kind)).await
+}
+
+/// Search AniList for a parsed title and return the best scoring candidate
+///
+/// # Returns
+/// * `Ok(None)` when the parse has no title or nothing scores above the threshold
+pub async fn match_parsed_title(parsed: &ParsedTitle) -> Result<Option<TitleMatch>, String> {
+    let title = match parsed.title.as_deref() {
+        Some(title) => title,
+        None => return Ok(None),
+    };
+
+    let candidates = search_candidates(title, parsed.kind).await?;
+    Ok(best_match(parsed, candidates))
+}
+
+/// The highest scoring candidate, if it reaches `MATCH_THRESHOLD`
+pub fn best_match(parsed: &ParsedTitle, candidates: Vec<Anime>) -> Option<TitleMatch> {
+    let found = candidates.len();
+    let mut best: Option<TitleMatch> = None;
+    for anime in candidates {
+        let score = score_candidate(parsed, &anime);
+        // Strictly greater, so the earlier (higher ranked) result keeps a tie
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(TitleMatch { anime, score });
+        }
+    }
+
+    let title = parsed.title.as_deref().unwrap_or_default();
+    match best.as_ref() {
+        Some(m) if m.score >= MATCH_THRESHOLD => println!(
+            "[Matcher] '{}' → {} ({:?}), score {:.2} of {} candidate(s)",
+            title, m.anime.id, m.anime.title.romaji, m.score, found
+        ),
+        _ => println!(
+            "[Matcher] '{}': none of {} candidate(s) scored {:.2}",
+            title, found, MATCH_THRESHOLD
+        ),
+    }
+    best.filter(|m| m.score >= MATCH_THRESHOLD)
+}
+
+/// Score how well `anime` fits the parse, 0.0 - 1.0
+pub fn score_candidate(parsed: &ParsedTitle, anime: &Anime) -> f64 {
+    let title = match parsed.title.as_deref() {
+        Some(title) => normalize(title),
+        None => return 0.0,
+    };
+
+    let mut total = TITLE_WEIGHT * title_score(&title, anime);
+    let mut weight = TITLE_WEIGHT;
+
+    total += FORMAT_WEIGHT * format_score(parsed.kind, anime.format.as_deref());
+    weight += FORMAT_WEIGHT;
+
+    if let Some(year) = parsed.year {
+        total += YEAR_WEIGHT * year_score(year, anime.year());
+        weight += YEAR_WEIGHT;
+    }
+
+    if let Some(episode) = parsed.episode_range.map(|r| r.end).or(parsed.episode) {
+        total += EPISODE_WEIGHT * episode_score(episode, anime);
+        weight += EPISODE_WEIGHT;
+    }
+
+    total / weight
+}
+
+/// Similarity to the closest of the candidate's titles and synonyms
+fn title_score(title: &str, anime: &Anime) -> f64 {
+    let names = [
+        anime.title.romaji.as_deref(),
+        anime.title.english.as_deref(),
+        anime.title.native.as_deref(),
+    ];
+    names
+        .into_iter()
+        .flatten()
+        .chain(anime.synonyms.iter().map(String::as_str))
+        .map(|name| similarity(title, &normalize(name)))
+        .fold(0.0, f64::max)
+}
+
+/// How well the AniList format fits the parsed kind
+fn format_score(kind: MediaKind, format: Option<&str>) -> f64 {
+    let format = match format {
+        Some(format) => format,
+        None => return 0.5,
+    };
+    let expected = search_formats(kind);
+    if expected.is_empty() {
+        // Numbered episodes belong to a series; a movie or OVA is still possible
+        if SERIES_FORMATS.contains(&format) {
+            1.0
+        } else {
+            0.5
+        }
+    } else if expected.contains(&format) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// How close `Anime::year` is to the parsed year (one off still counts for most)
+fn year_score(year: i32, anime_year: Option<i32>) -> f64 {
+    match anime_year.map(|y| (y - year).abs()) {
+        Some(0) => 1.0,
+        Some(1) => 0.6,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+/// An episode past the entry's count only fits an airing entry, or a later
+/// season reached through absolute numbering (see `episode_mapper`)
+fn episode_score(episode: i32, anime: &Anime) -> f64 {
+    match anime.episodes {
+        Some(count) if episode > count => {
+            let is_series = anime
+                .format
+                .as_deref()
+                .is_some_and(|f| SERIES_FORMATS.contains(&f));
+            if is_series {
+                0.5
+            } else {
+                0.0
+            }
+        }
+        _ => 1.0,
+    }
+}
+
+/// Lowercase, full-width folded words without punctuation
+///
+/// `Re:Zero` and `Re Zero`, `HUNTER×HUNTER` and `Hunter x Hunter` come out the same.
+pub fn normalize(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfkc().flat_map(char::to_lowercase) {
+        match c {
+            '×' => folded.push_str(" x "),
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => folded.push(' '),
+        }
+    }
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Similarity of two normalized titles, 0.0 - 1.0
+///
+/// The Dice coefficient of their character bigrams, raised when every word
+/// of `query` appears in `name`: releases often use a shortened title
+/// ("Frieren" for "Sousou no Frieren").
+pub fn similarity(query: &str, name: &str) -> f64 {
+    if query.is_empty() || name.is_empty() {
+        return 0.0;
+    }
+    if query == name {
+        return 1.0;
+    }
+
+    let dice = bigram_dice(query, name);
+    let name_words: HashSet<&str> = name.split_whitespace().collect();
+    if query
+        .split_whitespace()
+        .all(|word| name_words.contains(word))
+    {
+        // Below an exact match, so "Overlord" still prefers "Overlord" to "Overlord II"
+        dice.max(0.85)
+    } else {
+        dice
+    }
+}
+
+/// Sørensen-Dice coefficient over character bigrams (with repeats)
+fn bigram_dice(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len() < 2 || b.len() < 2 {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+
+    let mut remaining: Vec<(char, char)> = b.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = (a.len() - 1) + remaining.len();
+    let mut shared = 0;
+    for pair in a.windows(2).map(|w| (w[0], w[1])) {
+        if let Some(i) = remaining.iter().position(|p| *p == pair) {
+            remaining.swap_remove(i);
+            shared += 1;
+        }
+    }
+    (2 * shared) as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anilist::{AnimeTitle, CoverImage};
+
+    fn anime(id: i32, romaji: &str, format: &str, episodes: Option<i32>, year: i32) -> Anime {
+        Anime {
+            id,
+            id_mal: None,
+            title: AnimeTitle {
+                romaji: Some(romaji.to_string()),
+                english: None,
+                native: None,
+            },
+            cover_image: CoverImage {
+                large: None,
+                medium: None,
+            },
+            episodes,
+            format: Some(format.to_string()),
+            status: None,
+            description: None,
+            season_year: Some(year),
+            start_date: None,
+            synonyms: Vec::new(),
+        }
+    }
+
+    fn parsed(title: &str, episode: Option<i32>, year: Option<i32>) -> ParsedTitle {
+        ParsedTitle {
+            title: Some(title.to_string()),
+            episode,
+            year,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_and_similarity() {
+        assert_eq!(
+            normalize("Re:ZERO -Starting Life-"),
+            "re zero starting life"
+        );
+        assert_eq!(normalize("HUNTER×HUNTER (2011)"), "hunter x hunter 2011");
+        assert_eq!(normalize("ＳＰＹ×ＦＡＭＩＬＹ"), "spy x family");
+        assert_eq!(similarity("overlord", "overlord"), 1.0);
+        assert!(similarity("frieren", "sousou no frieren") >= 0.85);
+        assert!(similarity("overlord", "overlord ii") < 1.0);
+        assert!(similarity("one piece", "naruto") < 0.2);
+    }
+
+    #[test]
+    fn test_year_picks_remake() {
+        let candidates = vec![
+            anime(136, "HUNTER×HUNTER", "TV", Some(62), 1999),
+            anime(11061, "HUNTER×HUNTER (2011)", "TV", Some(148), 2011),
+        ];
+        let best = best_match(
+            &parsed("Hunter x Hunter", Some(5), Some(2011)),
+            candidates.clone(),
+        );
+        assert_eq!(best.map(|m| m.anime.id), Some(11061));
+
+        // Without a year the exact title wins
+        let best = best_match(&parsed("Hunter x Hunter", Some(5), None), candidates);
+        assert_eq!(best.map(|m| m.anime.id), Some(136));
+    }
+
+    #[test]
+    fn test_synonyms_and_episode_counts() {
+        let mut frieren = anime(154587, "Sousou no Frieren", "TV", Some(28), 2023);
+        frieren.synonyms = vec!["Frieren: Beyond Journey's End".to_string()];
+        let movie = anime(1, "Frieren Movie", "MOVIE", Some(1), 2023);
+
+        let best = best_match(&parsed("Frieren", Some(12), None), vec![movie, frieren]);
+        let best = best.unwrap();
+        assert_eq!(best.anime.id, 154587);
+        assert!(best.score >= MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn test_below_threshold() {
+        let candidates = vec![anime(21, "One Piece", "TV", None, 1999)];
+        assert!(best_match(&parsed("Bocchi the Rock", Some(3), None), candidates).is_none());
+        assert!(best_match(&parsed("Anything", None, None), Vec::new()).is_none());
+    }
+}
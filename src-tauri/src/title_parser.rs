@@ -3,19 +3,364 @@
 /// PURPOSE: Parse anime titles and episode numbers from window titles
 /// Handles common anime filename formats from VLC, MPV, MPC, etc.
 ///
-/// APPROACH: Use regex patterns to extract structured data
+/// APPROACH: Split the title into tokens once (see `title_tokenizer`), then
+/// classify the tokens: release tags, non-episode markers and episode numbers
+use crate::parsing_rules;
+use crate::site_grammars::{self, SiteTitle};
+use crate::title_tokenizer::{self, Token, TokenKind};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use unicode_normalization::UnicodeNormalization;
+
+/// Known video file extensions (lowercase, with dot)
+const FILE_EXTENSIONS: [&str; 8] = [
+    ".mkv", ".mp4", ".avi", ".webm", ".m4v", ".mov", ".wmv", ".flv",
+];
+
+/// Longest span accepted as an episode range; anything wider is a misparse
+const MAX_RANGE_LEN: i32 = 100;
+
+lazy_static::lazy_static! {
+    /// `第05話`, `第5话`, `05話`, `第十二集`
+    static ref NATIVE_EPISODE: Regex =
+        Regex::new(r"第?\s*(\d{1,4}(?:\.\d)?|[〇一二三四五六七八九十]{1,3})\s*[話话回集]").unwrap();
+
+    /// `第2期`, `第二季`, `2期`, `シーズン2`
+    static ref NATIVE_SEASON: Regex = Regex::new(
+        r"\s*(?:第\s*(\d{1,2}|[一二三四五六七八九十]{1,3})\s*[期季]|(\d{1,2})\s*期|シーズン\s*(\d{1,2}))",
+    )
+    .unwrap();
+
+    /// A file name that is only an episode: `05`, `E05v2`, `第05話`, `05 - Subtitle`
+    static ref BARE_EPISODE: Regex = Regex::new(
+        r"(?i)^(?:E|Ep\.?|Episode|第)?\s*(\d{1,3}(?:\.\d)?)(?:v\d{1,2})?\s*[話话集]?(?:\s*-\s*[^\d\s].*)?$",
+    )
+    .unwrap();
+
+    /// A whole folder name that is a season: `Season 2`, `S02`, `2nd Season`, `第2期`
+    static ref SEASON_FOLDER: Regex = Regex::new(
+        r"(?i)^(?:(?:season|series|s)\s*(\d{1,2})|(\d{1,2})(?:st|nd|rd|th)\s+season|第?\s*(\d{1,2}|[一二三四五六七八九十]{1,3})\s*[期季]|シーズン\s*(\d{1,2}))$",
+    )
+    .unwrap();
+
+    /// A season inside a series folder name: `Mushoku Tensei S2`
+    static ref FOLDER_SEASON: Regex = Regex::new(
+        r"(?i)\s*\b(?:season\s*(\d{1,2})|(\d{1,2})(?:st|nd|rd|th)\s+season|S(\d{1,2}))\b",
+    )
+    .unwrap();
+}
+
+/// First and last episode of a multi-episode file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EpisodeRange {
+    pub start: i32,
+    pub end: i32,
+}
+
+/// What kind of media a file is
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    /// Regular numbered episode
+    #[default]
+    Episode,
+    /// Special / omake / `SP03` / `S00E03`
+    Special,
+    Ova,
+    Ona,
+    Movie,
+    /// Creditless opening (`NCOP`, `OP1`)
+    Opening,
+    /// Creditless ending (`NCED`, `ED2`)
+    Ending,
+    /// Preview, trailer, PV or CM
+    Preview,
+}
+
+impl MediaKind {
+    /// Whether files of this kind can ever move list progress
+    pub fn is_countable(&self) -> bool {
+        !matches!(
+            self,
+            MediaKind::Opening | MediaKind::Ending | MediaKind::Preview
+        )
+    }
+}
+
+/// Whether a non-episode marker carries a number ("OVA 2", "NCOP1")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkerNumber {
+    Never,
+    Optional,
+    Required,
+}
+
+/// Words marking non-episode media
+struct KindMarker {
+    kind: MediaKind,
+    /// Phrases with their words separated by single spaces
+    phrases: &'static [&'static str],
+    /// Compare case-sensitively: lower-case "op" and "ed" are ordinary words
+    match_case: bool,
+    number: MarkerNumber,
+}
+
+const fn marker(
+    kind: MediaKind,
+    phrases: &'static [&'static str],
+    match_case: bool,
+    number: MarkerNumber,
+) -> KindMarker {
+    KindMarker {
+        kind,
+        phrases,
+        match_case,
+        number,
+    }
+}
+
+/// Words marking a remake; dropped from the title like a year
+const REMAKE_MARKERS: [&str; 2] = ["remake", "reboot"];
+
+/// Markers for non-episode media, checked in order
+const MEDIA_KIND_MARKERS: [KindMarker; 11] = [
+    marker(
+        MediaKind::Opening,
+        &["ncop", "nc op", "creditless opening"],
+        false,
+        MarkerNumber::Optional,
+    ),
+    marker(MediaKind::Opening, &["OP"], true, MarkerNumber::Optional),
+    marker(
+        MediaKind::Ending,
+        &["nced", "nc ed", "creditless ending"],
+        false,
+        MarkerNumber::Optional,
+    ),
+    marker(MediaKind::Ending, &["ED"], true, MarkerNumber::Optional),
+    marker(
+        MediaKind::Preview,
+        &["pv", "cm", "preview", "trailer", "teaser"],
+        false,
+        MarkerNumber::Optional,
+    ),
+    marker(
+        MediaKind::Ova,
+        &["ova", "oad"],
+        false,
+        MarkerNumber::Optional,
+    ),
+    marker(MediaKind::Ona, &["ona"], false, MarkerNumber::Optional),
+    marker(
+        MediaKind::Movie,
+        &["the movie", "movie", "gekijouban", "gekijoban"],
+        false,
+        MarkerNumber::Never,
+    ),
+    marker(MediaKind::Special, &["sp"], false, MarkerNumber::Required),
+    marker(
+        MediaKind::Special,
+        &["special", "specials"],
+        false,
+        MarkerNumber::Optional,
+    ),
+    marker(
+        MediaKind::Special,
+        &["omake"],
+        false,
+        MarkerNumber::Optional,
+    ),
+];
+
+impl KindMarker {
+    fn same(&self, word: &str, part: &str) -> bool {
+        if self.match_case {
+            word == part
+        } else {
+            word.eq_ignore_ascii_case(part)
+        }
+    }
+
+    /// Match the marker at the start of `words`
+    ///
+    /// Returns the marker's number and how many words it spans.
+    fn match_words(&self, words: &[&str]) -> Option<(Option<i32>, usize)> {
+        for phrase in self.phrases {
+            let len = phrase.split(' ').count();
+            if words.len() < len {
+                continue;
+            }
+
+            // The number may be glued to the last word ("NCOP1", "SP03")
+            let mut glued = None;
+            let matched = phrase
+                .split(' ')
+                .zip(words)
+                .enumerate()
+                .all(|(i, (part, word))| {
+                    if i + 1 < len {
+                        return self.same(word, part);
+                    }
+                    match word.get(..part.len()) {
+                        Some(head) if self.same(head, part) => {
+                            let tail = &word[part.len()..];
+                            if tail.is_empty() {
+                                true
+                            } else if self.number != MarkerNumber::Never && is_number(tail, 2) {
+                                glued = tail.parse().ok();
+                                true
+                            } else {
+                                false
+                            }
+                        }
+                        _ => false,
+                    }
+                });
+            if !matched {
+                continue;
+            }
+
+            let mut number = glued;
+            let mut span = len;
+            if number.is_none() && self.number != MarkerNumber::Never {
+                if let Some(next) = words.get(len).filter(|w| is_number(w, 2)) {
+                    number = next.parse().ok();
+                    span += 1;
+                }
+            }
+            if self.number == MarkerNumber::Required && number.is_none() {
+                continue;
+            }
+            return Some((number, span));
+        }
+        None
+    }
+}
 
 /// Result of parsing a window title
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ParsedTitle {
     /// Cleaned anime title (if detected)
     pub title: Option<String>,
-    /// Episode number (if detected)
+    /// Episode number to credit (the last one for multi-episode files)
     pub episode: Option<i32>,
     /// Season number (if detected)
     pub season: Option<i32>,
+    /// Release year from `(2011)` or a year ending the title, to tell remakes apart
+    #[serde(default)]
+    pub year: Option<i32>,
+    /// `Remake` / `Reboot` marker
+    #[serde(default)]
+    pub is_remake: bool,
+    /// Episode span for files like "01-12" or "E01+E02"
+    #[serde(default)]
+    pub episode_range: Option<EpisodeRange>,
+    /// Fractional episode number (recaps like "12.5"); `episode` stays empty
+    #[serde(default)]
+    pub episode_decimal: Option<f64>,
+    /// Batch release (a whole season under one name); `episode` stays empty
+    #[serde(default)]
+    pub is_batch: bool,
+    /// Episode, special, OVA, movie, creditless OP/ED or preview
+    #[serde(default)]
+    pub kind: MediaKind,
+    /// Release group, e.g. "SubsPlease" from `[SubsPlease]`
+    #[serde(default)]
+    pub release_group: Option<String>,
+    /// Vertical resolution, e.g. "1080p"
+    #[serde(default)]
+    pub resolution: Option<String>,
+    /// Video codec: HEVC, AVC, AV1, VP9
+    #[serde(default)]
+    pub video_codec: Option<String>,
+    /// Color depth in bits (10 for `10bit` / `Hi10P`)
+    #[serde(default)]
+    pub bit_depth: Option<i32>,
+    /// Audio codec: AAC, FLAC, Opus, AC3, EAC3, DTS, TrueHD, MP3
+    #[serde(default)]
+    pub audio_codec: Option<String>,
+    /// Release source: BD, WEB, DVD, TV
+    #[serde(default)]
+    pub source: Option<String>,
+    /// CRC32 checksum tag, uppercase (e.g. "ABCD1234")
+    #[serde(default)]
+    pub crc32: Option<String>,
+    /// Release version from `v2`-style tags
+    #[serde(default)]
+    pub version: Option<i32>,
+    /// File extension without the dot, lowercase
+    #[serde(default)]
+    pub file_extension: Option<String>,
+    /// Streaming site the tab belongs to ("crunchyroll", "hianime", ...)
+    #[serde(default)]
+    pub site: Option<String>,
+}
+
+/// Below this a parse is a guess: callers should ask the user instead of matching
+pub const MIN_CONFIDENCE: f64 = 0.5;
+
+/// Penalty for a candidate without a title (restored when a folder supplies one)
+const NO_TITLE_PENALTY: f64 = 0.4;
+
+/// Which pattern produced a candidate
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseStrategy {
+    /// A pattern from the user's `parsing_rules.json`
+    UserPattern,
+    /// `S02E05`
+    SeasonEpisode,
+    /// `Episode 5`, `Ep 05`, `E05`
+    EpisodeKeyword,
+    /// `第05話`
+    NativeEpisode,
+    /// `Title - 05`, `[Group] Title - 05`
+    DashNumber,
+    /// `[Group][Title][05]`
+    BracketedNumber,
+    /// `Anime_Title_01.mkv`, `Title 05 [1080p].mkv`
+    TrailingNumber,
+    /// `05.mkv` inside a series folder
+    BareNumber,
+    /// A streaming site's tab title format (see `site_grammars`)
+    SiteGrammar,
+    /// No episode marker; the whole string is the title
+    #[default]
+    Fallback,
+}
+
+impl ParseStrategy {
+    /// Base trust: explicit markers beat positional guesses
+    fn base_confidence(self) -> f64 {
+        match self {
+            ParseStrategy::UserPattern => 1.0,
+            ParseStrategy::SeasonEpisode => 0.95,
+            ParseStrategy::SiteGrammar => 0.9,
+            ParseStrategy::EpisodeKeyword | ParseStrategy::NativeEpisode => 0.85,
+            ParseStrategy::BareNumber => 0.8,
+            ParseStrategy::DashNumber => 0.75,
+            ParseStrategy::BracketedNumber => 0.65,
+            ParseStrategy::TrailingNumber => 0.6,
+            ParseStrategy::Fallback => 0.2,
+        }
+    }
+}
+
+/// One parse candidate and how far it can be trusted
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScoredParse {
+    pub parsed: ParsedTitle,
+    /// 0.0 (guess) - 1.0 (certain)
+    pub confidence: f64,
+    pub strategy: ParseStrategy,
+}
+
+/// Best candidate plus the other readings, most confident first
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ParseResult {
+    pub best: ScoredParse,
+    pub alternatives: Vec<ScoredParse>,
 }
 
 /// Parse anime title and episode from a window title
@@ -30,73 +375,763 @@ pub struct ParsedTitle {
 /// - `[SubGroup] Anime Title - 05 [1080p].mkv - VLC media player`
 /// - `Anime Title S02E05.mkv - mpv`
 /// - `Anime Title Episode 12 - MPC-HC`
-/// - `Anime Title - 05.mp4`
+/// - `Anime Title - 05.mp4`, `Anime Title - 05 - Episode Title.mkv`
 /// - `Anime_Title_01.mkv` (underscores as spaces)
 /// - `Anime.Title.01.mkv` (dots as spaces)
+/// - `Anime Title - 12.5.mkv` (recap, no creditable episode)
+/// - `Anime Title S01E01+E02.mkv` / `Anime Title - 01-12 [Batch]` (ranges)
+/// - `Anime Title - 05v2.mkv` (release version)
+/// - `Anime Title - OVA 2.mkv`, `Anime Title SP03.mkv`, `Anime Title - NCOP1.mkv` (non-episode media)
+/// - `葬送のフリーレン 第2期 第05話.mkv`, `[字幕组][葬送的芙莉莲][05][1080P].mp4`, `【推しの子】 第０５話` (CJK)
 pub fn parse_window_title(window_title: &str) -> ParsedTitle {
+    parse_window_title_ranked(window_title).best.parsed
+}
+
+/// Parse a window title with every strategy and rank the candidates
+///
+/// # Returns
+/// * `ParseResult` with the best candidate and the alternatives, each scored
+pub fn parse_window_title_ranked(window_title: &str) -> ParseResult {
+    rank(window_title_candidates(window_title))
+}
+
+/// Run the full pipeline once per strategy that matches
+fn window_title_candidates(window_title: &str) -> Vec<ScoredParse> {
+    // Fold full-width characters and CJK brackets so the patterns below apply
+    let folded = normalize_width(window_title);
+
+    // Browser tabs: drop the browser/profile suffix, then try the site grammars
+    let folded = site_grammars::strip_browser_suffix(&folded);
+
+    let mut candidates = user_pattern_candidates(&folded);
+    match site_grammars::match_site(&folded) {
+        Some(site) => candidates.extend(site_candidates(site)),
+        None => candidates.extend(generic_candidates(&folded)),
+    }
+    candidates
+}
+
+/// Candidates from the user's custom patterns (named groups `title`, `episode`, `season`)
+fn user_pattern_candidates(folded: &str) -> Vec<ScoredParse> {
+    let patterns = parsing_rules::custom_patterns();
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+
+    let cleaned = remove_player_suffix(folded);
+    let (body, file_extension) = title_tokenizer::split_extension(&cleaned, &FILE_EXTENSIONS);
+    let release = release_info(&title_tokenizer::tokenize(body), file_extension);
+
+    patterns
+        .iter()
+        .filter_map(|re| {
+            let caps = re.captures(&cleaned)?;
+            let number = |group: &str| caps.name(group).and_then(|m| m.as_str().parse().ok());
+            let mut parsed = ParsedTitle {
+                title: caps
+                    .name("title")
+                    .map(|m| clean_title(m.as_str()))
+                    .filter(|t| !t.is_empty()),
+                season: number("season"),
+                episode: number("episode"),
+                ..release.clone()
+            };
+            extract_year(&mut parsed);
+
+            Some(ScoredParse {
+                confidence: score(ParseStrategy::UserPattern, &parsed),
+                parsed,
+                strategy: ParseStrategy::UserPattern,
+            })
+        })
+        .collect()
+}
+
+/// Candidates for a streaming-site tab: the grammar's own reading plus the
+/// generic parser's readings of the boilerplate-free text
+fn site_candidates(site: SiteTitle) -> Vec<ScoredParse> {
+    let mut candidates = if site.text.is_empty() {
+        Vec::new()
+    } else {
+        generic_candidates(&site.text)
+    };
+
+    if site.is_structured() {
+        let mut parsed = ParsedTitle {
+            title: site.title.clone(),
+            season: site.season,
+            episode: site.episode,
+            ..Default::default()
+        };
+        extract_year(&mut parsed);
+        candidates.push(ScoredParse {
+            confidence: score(ParseStrategy::SiteGrammar, &parsed),
+            parsed,
+            strategy: ParseStrategy::SiteGrammar,
+        });
+    }
+
+    // A bare "Netflix" tab still says where the user is
+    if candidates.is_empty() {
+        candidates.push(ScoredParse::default());
+    }
+    for candidate in candidates.iter_mut() {
+        candidate.parsed.site = Some(site.site.to_string());
+    }
+    candidates
+}
+
+/// The generic pipeline for player windows and file names
+fn generic_candidates(folded: &str) -> Vec<ScoredParse> {
     // First, remove the media player suffix
-    let cleaned = remove_player_suffix(window_title);
+    let cleaned = remove_player_suffix(folded);
+    let (body, file_extension) = title_tokenizer::split_extension(&cleaned, &FILE_EXTENSIONS);
+
+    // One pass over the characters; everything below works on the tokens
+    let tokens = title_tokenizer::tokenize(body);
+    let release = release_info(&tokens, file_extension);
+
+    // Take out "OVA 2", "NCOP1", "The Movie"... so they don't read as episode numbers
+    let (kind, kind_number, tokens) = split_media_kind(tokens);
+
+    title_and_episode_candidates(&tokens, release.file_extension.is_some())
+        .into_iter()
+        .map(|(strategy, mut result)| {
+            extract_native_season(&mut result);
+            apply_media_kind(&mut result, kind, kind_number);
+
+            let mut result = ParsedTitle {
+                title: result.title,
+                episode: result.episode,
+                season: result.season,
+                episode_range: result.episode_range,
+                episode_decimal: result.episode_decimal,
+                kind: result.kind,
+                ..release.clone()
+            };
+            extract_year(&mut result);
+
+            // A batch covers many episodes; crediting its last one would be a bad update
+            if result.is_batch {
+                result.episode = None;
+            }
+
+            ScoredParse {
+                confidence: score(strategy, &result),
+                parsed: result,
+                strategy,
+            }
+        })
+        .collect()
+}
 
-    // Normalize separators: treat _ and . as spaces (common in filenames)
-    let normalized = normalize_separators(&cleaned);
+/// Score a finished candidate: the strategy's base trust, adjusted for how
+/// plausible the extracted title looks
+fn score(strategy: ParseStrategy, parsed: &ParsedTitle) -> f64 {
+    let mut confidence = strategy.base_confidence();
 
-    // Try different parsing strategies
-    if let Some(result) = try_parse_season_episode(&normalized) {
-        return result;
+    match parsed.title.as_deref() {
+        None => confidence -= NO_TITLE_PENALTY,
+        Some(title) if !title.chars().any(char::is_alphanumeric) => confidence -= NO_TITLE_PENALTY,
+        // Leftover brackets or a sentence-length "title" mean the split went wrong
+        Some(title) if title.contains(['[', ']']) || title.chars().count() > 80 => {
+            confidence -= 0.15
+        }
+        Some(_) => {}
     }
 
-    if let Some(result) = try_parse_episode_keyword(&normalized) {
-        return result;
+    // "Movie", "OVA" or "NCOP" markers explain a missing episode number
+    if strategy == ParseStrategy::Fallback && parsed.kind != MediaKind::Episode {
+        confidence += 0.35;
     }
 
-    if let Some(result) = try_parse_dash_number(&normalized) {
-        return result;
+    // Series pages name the show but not what is playing
+    if strategy == ParseStrategy::SiteGrammar && parsed.episode.is_none() {
+        confidence -= 0.45;
     }
 
-    if let Some(result) = try_parse_bracketed(&normalized) {
-        return result;
+    // File names (rather than page or stream titles) follow release conventions
+    if parsed.file_extension.is_some() || parsed.release_group.is_some() {
+        confidence += 0.05;
     }
 
-    // Fallback: just clean the title
-    ParsedTitle {
-        title: Some(clean_title(&normalized)),
-        episode: None,
-        season: None,
+    confidence.clamp(0.0, 1.0)
+}
+
+/// Apply the user's title rewrites, sort candidates by confidence (ties keep
+/// strategy order) and drop duplicate readings
+fn rank(mut candidates: Vec<ScoredParse>) -> ParseResult {
+    for candidate in candidates.iter_mut() {
+        let rewritten = candidate
+            .parsed
+            .title
+            .as_deref()
+            .and_then(parsing_rules::rewrite_title);
+        if rewritten.is_some() {
+            candidate.parsed.title = rewritten;
+        }
+    }
+
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut unique: Vec<ScoredParse> = Vec::new();
+    for candidate in candidates {
+        if !unique.iter().any(|u| u.parsed == candidate.parsed) {
+            unique.push(candidate);
+        }
+    }
+
+    let mut iter = unique.into_iter();
+    ParseResult {
+        best: iter.next().unwrap_or_default(),
+        alternatives: iter.collect(),
     }
 }
 
-/// Normalize common filename separators to spaces
-/// Converts underscores and dots to spaces (except dots in file extensions)
-fn normalize_separators(title: &str) -> String {
-    let mut result = title.to_string();
+/// Find a non-episode marker after the first word and take it out of the tokens
+///
+/// Markers at the very start of the title (after a `[Group]` tag) are part of
+/// the name ("Special A - 05"), not a classification.
+fn split_media_kind(tokens: Vec<Token>) -> (MediaKind, Option<i32>, Vec<Token>) {
+    let word_indices: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.kind == TokenKind::Word)
+        .map(|(i, _)| i)
+        .collect();
+    let words: Vec<&str> = word_indices.iter().map(|&i| tokens[i].text).collect();
 
-    // First, protect file extensions by temporarily replacing them
-    let extensions = [
-        ".mkv", ".mp4", ".avi", ".webm", ".m4v", ".mov", ".wmv", ".flv",
+    let found = MEDIA_KIND_MARKERS.iter().find_map(|marker| {
+        (1..words.len()).find_map(|at| {
+            marker
+                .match_words(&words[at..])
+                .map(|(number, span)| (marker.kind, number, at, span))
+        })
+    });
+    let (kind, number, at, span) = match found {
+        Some(found) => found,
+        None => return (MediaKind::Episode, None, tokens),
+    };
+
+    let removed = &word_indices[at..at + span];
+    let rest = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !removed.contains(i))
+        .map(|(_, t)| *t);
+    (kind, number, merge_delimiters(rest))
+}
+
+/// Collapse delimiters left next to each other, keeping a dash if there is one
+fn merge_delimiters<'a>(tokens: impl Iterator<Item = Token<'a>>) -> Vec<Token<'a>> {
+    let mut merged: Vec<Token<'a>> = Vec::new();
+    for token in tokens {
+        match merged.last_mut() {
+            Some(last)
+                if last.kind == TokenKind::Delimiter && token.kind == TokenKind::Delimiter =>
+            {
+                if !is_dash(last) && is_dash(&token) {
+                    *last = token;
+                }
+            }
+            _ => merged.push(token),
+        }
+    }
+    merged
+}
+
+/// Set the kind and fix up episode fields for non-episode media
+fn apply_media_kind(parsed: &mut ParsedTitle, kind: MediaKind, number: Option<i32>) {
+    // "S00E03" is how most tools name specials
+    let kind = if kind == MediaKind::Episode && parsed.season == Some(0) {
+        MediaKind::Special
+    } else {
+        kind
+    };
+    parsed.kind = kind;
+
+    match kind {
+        MediaKind::Episode => {}
+        MediaKind::Opening | MediaKind::Ending | MediaKind::Preview => {
+            parsed.episode = None;
+            parsed.episode_range = None;
+        }
+        // A movie is the only "episode" of its own AniList entry
+        MediaKind::Movie => {
+            parsed.episode = Some(1);
+            parsed.episode_range = None;
+        }
+        // Numbered within their own AniList entry ("OVA 2" → episode 2)
+        MediaKind::Special | MediaKind::Ova | MediaKind::Ona => {
+            if number.is_some() || parsed.episode.is_none() {
+                parsed.episode = Some(number.unwrap_or(1));
+                parsed.episode_range = None;
+            }
+        }
+    }
+}
+
+/// Fill the episode fields from a strategy's captured start/end numbers
+///
+/// Returns None if `start` isn't a number.
+fn set_episodes(parsed: &mut ParsedTitle, start: &str, end: Option<&str>) -> Option<()> {
+    let first: f64 = start.parse().ok()?;
+    if first.fract() != 0.0 {
+        parsed.episode_decimal = Some(first);
+        return Some(());
+    }
+
+    let first = first as i32;
+    let last = end
+        .and_then(|e| e.parse::<i32>().ok())
+        .filter(|&last| last > first && last - first <= MAX_RANGE_LEN);
+
+    parsed.episode = Some(last.unwrap_or(first));
+    parsed.episode_range = last.map(|end| EpisodeRange { start: first, end });
+    Some(())
+}
+
+/// Parse a full file path, taking whatever the file name doesn't say from
+/// the parent and grandparent folders
+///
+/// # Supported Layouts
+/// - `/Anime/Mushoku Tensei/Season 2/05.mkv` (series folder + season folder + bare number)
+/// - `D:\Anime\[Group] Frieren S2 [1080p]\Episode 05.mkv` (tagged series folder)
+/// - `/Anime/Frieren/Specials/02.mkv` (specials folder)
+/// - `/Anime/Frieren/[SubsPlease] Frieren - 05 (1080p).mkv` (file name wins)
+pub fn parse_media_path(path: &str) -> ParsedTitle {
+    parse_media_path_ranked(path).best.parsed
+}
+
+/// Like `parse_media_path`, with confidence scores and ranked alternatives
+pub fn parse_media_path_ranked(path: &str) -> ParseResult {
+    let parts: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    let (file_name, folders) = match parts.split_last() {
+        Some(split) => split,
+        None => return ParseResult::default(),
+    };
+    let parent = folders.last().copied();
+    let grandparent = folders.iter().rev().nth(1).copied();
+
+    // A "Season 2" / "Specials" folder sits between the series folder and the file
+    let season_folder = parent.and_then(parse_season_folder);
+    let series_folder = if season_folder.is_some() {
+        grandparent
+    } else {
+        parent
+    };
+
+    let series = series_folder
+        .filter(|f| !is_generic_folder(f))
+        .map(parse_series_folder);
+
+    let mut candidates = parse_file_name(file_name);
+    for candidate in candidates.iter_mut() {
+        let result = &mut candidate.parsed;
+        match season_folder {
+            Some((_, Some(season))) if result.season.is_none() => result.season = Some(season),
+            Some((kind, None)) if result.kind == MediaKind::Episode => {
+                result.kind = kind;
+                result.episode = result.episode.or(Some(1));
+            }
+            _ => {}
+        }
+
+        if let Some(series) = series.as_ref() {
+            if result.title.is_none() && series.title.is_some() {
+                result.title = series.title.clone();
+                candidate.confidence = (candidate.confidence + NO_TITLE_PENALTY).min(1.0);
+            }
+            result.season = result.season.or(series.season);
+            result.year = result.year.or(series.year);
+            result.is_remake |= series.is_remake;
+            result.release_group = result.release_group.take().or(series.release_group.clone());
+            result.resolution = result.resolution.take().or(series.resolution.clone());
+            result.source = result.source.take().or(series.source.clone());
+            result.video_codec = result.video_codec.take().or(series.video_codec.clone());
+            result.audio_codec = result.audio_codec.take().or(series.audio_codec.clone());
+        }
+    }
+
+    rank(candidates)
+}
+
+/// Parse a file name on its own; bare names like `05.mkv`, `E05.mkv` or
+/// `05 - Subtitle.mkv` add an episode-only candidate
+fn parse_file_name(file_name: &str) -> Vec<ScoredParse> {
+    let mut candidates = window_title_candidates(file_name);
+
+    let stem = clean_title(&normalize_width(file_name));
+    let number = BARE_EPISODE.captures(&stem).and_then(|caps| caps.get(1));
+    // Every candidate shares the release info and kind; start from any of them
+    if let (Some(number), Some(first)) = (number, candidates.first()) {
+        let mut parsed = ParsedTitle {
+            title: None,
+            episode: None,
+            episode_range: None,
+            episode_decimal: None,
+            ..first.parsed.clone()
+        };
+        if set_episodes(&mut parsed, number.as_str(), None).is_some() {
+            candidates.push(ScoredParse {
+                confidence: score(ParseStrategy::BareNumber, &parsed),
+                parsed,
+                strategy: ParseStrategy::BareNumber,
+            });
+        }
+    }
+
+    candidates
+}
+
+/// Recognize season folders: "Season 2", "S02", "2nd Season", "第2期" → (Episode, Some(2));
+/// "Specials" / "OVA" → (Special/Ova, None)
+fn parse_season_folder(folder: &str) -> Option<(MediaKind, Option<i32>)> {
+    let folder = normalize_width(folder);
+    let folder = folder.trim();
+
+    if let Some(caps) = SEASON_FOLDER.captures(folder) {
+        let season = caps
+            .iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .and_then(|m| parse_native_number(m.as_str()));
+        return Some((MediaKind::Episode, season));
+    }
+
+    match folder.to_lowercase().as_str() {
+        "specials" | "special" | "sp" | "sps" | "omake" => Some((MediaKind::Special, None)),
+        "ova" | "ovas" | "oad" => Some((MediaKind::Ova, None)),
+        _ => None,
+    }
+}
+
+/// Parse a series folder name such as `[Group] Mushoku Tensei S2 [BD 1080p]`
+fn parse_series_folder(folder: &str) -> ParsedTitle {
+    let mut parsed = parse_window_title(folder);
+
+    // Folder names carry any number of tag brackets; the title is what's left
+    let title = clean_title(&normalize_width(folder));
+
+    if let Some(caps) = FOLDER_SEASON.captures(&title) {
+        parsed.season = parsed.season.or_else(|| {
+            caps.iter()
+                .skip(1)
+                .flatten()
+                .next()
+                .and_then(|m| m.as_str().parse().ok())
+        });
+    }
+    let title = FOLDER_SEASON.replace(&title, " ").trim().to_string();
+
+    parsed.title = Some(title).filter(|t| !t.is_empty()).or(parsed.title);
+    extract_year(&mut parsed);
+    parsed
+}
+
+/// Library root folders that never name a series
+fn is_generic_folder(folder: &str) -> bool {
+    matches!(
+        folder.trim().to_lowercase().as_str(),
+        "anime"
+            | "videos"
+            | "video"
+            | "movies"
+            | "downloads"
+            | "download"
+            | "tv"
+            | "tv shows"
+            | "series"
+            | "shows"
+            | "media"
+            | "completed"
+            | "torrents"
+            | "home"
+            | "users"
+            | "mnt"
+            | "volumes"
+    )
+}
+
+/// A `find_*` function: one way of reading the episode out of the tokens
+type StrategyFn = fn(&[Token]) -> Option<ParsedTitle>;
+
+/// Run every parsing strategy; each match is a candidate
+fn title_and_episode_candidates(
+    tokens: &[Token],
+    is_file: bool,
+) -> Vec<(ParseStrategy, ParsedTitle)> {
+    let strategies: [(ParseStrategy, StrategyFn); 5] = [
+        (ParseStrategy::SeasonEpisode, find_season_episode),
+        (ParseStrategy::EpisodeKeyword, find_episode_keyword),
+        (ParseStrategy::NativeEpisode, find_native_episode),
+        (ParseStrategy::DashNumber, find_dash_number),
+        (ParseStrategy::BracketedNumber, find_bracketed_number),
     ];
-    let mut ext_found = String::new();
-    for ext in &extensions {
-        if result.to_lowercase().ends_with(ext) {
-            ext_found = ext.to_string();
-            result = result[..result.len() - ext.len()].to_string();
-            break;
+
+    let mut candidates: Vec<(ParseStrategy, ParsedTitle)> = strategies
+        .iter()
+        .filter_map(|(strategy, find)| find(tokens).map(|result| (*strategy, result)))
+        .collect();
+
+    // "Title 05" is a release naming convention; in stream or page titles the
+    // number is too often part of the name
+    if is_file {
+        if let Some(result) = find_trailing_number(tokens) {
+            candidates.push((ParseStrategy::TrailingNumber, result));
         }
     }
 
-    // Replace underscores with spaces
-    result = result.replace('_', " ");
+    // Fallback: the words up to the first release tag
+    candidates.push((
+        ParseStrategy::Fallback,
+        ParsedTitle {
+            title: title_from_tokens(&tokens[..title_end(tokens)]),
+            ..Default::default()
+        },
+    ));
+    candidates
+}
 
-    // Replace dots with spaces (these are likely word separators in filenames)
-    result = result.replace('.', " ");
+/// Release tags seen so far; where a name lists several (`[HEVC][AVC]`), the
+/// lowest rank wins
+#[derive(Default)]
+struct ReleaseTags {
+    resolution: Option<(u8, String)>,
+    video_codec: Option<(u8, &'static str)>,
+    bit_depth: Option<(u8, i32)>,
+    audio_codec: Option<(u8, &'static str)>,
+    source: Option<(u8, &'static str)>,
+    crc32: Option<String>,
+    version: Option<i32>,
+    year: Option<i32>,
+    is_batch: bool,
+    is_remake: bool,
+}
 
-    // Clean up multiple spaces
-    let space_re = Regex::new(r"\s+").unwrap();
-    result = space_re.replace_all(&result, " ").to_string();
+/// Keep `value` unless one of the same or a lower rank was already seen
+fn keep<T>(slot: &mut Option<(u8, T)>, rank: u8, value: T) {
+    if !matches!(slot, Some((seen, _)) if *seen <= rank) {
+        *slot = Some((rank, value));
+    }
+}
 
-    // Add extension back (will be removed by clean_title later)
-    result.push_str(&ext_found);
+impl ReleaseTags {
+    /// Record the tags in every word, bracketed or not
+    fn scan(&mut self, tokens: &[Token]) {
+        for (i, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Word => {
+                    // "DD+" (the "+" is a delimiter)
+                    let plus_follows = tokens
+                        .get(i + 1)
+                        .is_some_and(|t| t.kind == TokenKind::Delimiter && t.text.starts_with('+'));
+                    self.scan_word(token.text, plus_follows);
+                }
+                TokenKind::Enclosed => {
+                    let content = token.text.trim();
+                    if is_crc32(content) && self.crc32.is_none() {
+                        self.crc32 = Some(content.to_ascii_uppercase());
+                    }
+                    // "(2011)", "[Remake]"
+                    if self.year.is_none() {
+                        self.year = parse_year(content);
+                    }
+                    if REMAKE_MARKERS
+                        .iter()
+                        .any(|m| content.eq_ignore_ascii_case(m))
+                    {
+                        self.is_remake = true;
+                    }
+                    if strip_prefix_ci(content, "complete")
+                        .is_some_and(|rest| !rest.starts_with(char::is_alphanumeric))
+                    {
+                        self.is_batch = true;
+                    }
+                    self.scan(&title_tokenizer::tokenize(content));
+                }
+                TokenKind::Delimiter => {}
+            }
+        }
+    }
 
-    result.trim().to_string()
+    /// Record a word's tags ("H.264-VARYG" is checked whole and by parts)
+    ///
+    /// Returns whether the word was a release tag.
+    fn scan_word(&mut self, word: &str, plus_follows: bool) -> bool {
+        let lower = word.to_ascii_lowercase();
+        let mut found = self.scan_tag(&lower, plus_follows);
+        if !found && lower.contains('-') {
+            for part in lower.split('-') {
+                found |= self.scan_tag(part, false);
+            }
+        }
+        found
+    }
+
+    /// Record a single lower-case tag; returns false for ordinary words
+    fn scan_tag(&mut self, tag: &str, plus_follows: bool) -> bool {
+        if let Some((rank, height)) = resolution_height(tag) {
+            keep(&mut self.resolution, rank, format!("{}p", height));
+            return true;
+        }
+
+        let video = match tag {
+            "hevc" | "x265" | "h265" | "x.265" | "h.265" => Some((0, "HEVC")),
+            "avc" | "x264" | "h264" | "x.264" | "h.264" => Some((1, "AVC")),
+            "av1" => Some((2, "AV1")),
+            "vp9" => Some((3, "VP9")),
+            _ => None,
+        };
+        if let Some((rank, codec)) = video {
+            keep(&mut self.video_codec, rank, codec);
+            return true;
+        }
+
+        let bits = match tag {
+            "10bit" | "10bits" | "10-bit" | "10-bits" => Some((0, 10)),
+            "8bit" | "8bits" | "8-bit" | "8-bits" => Some((0, 8)),
+            "hi10" | "hi10p" => Some((1, 10)),
+            _ => None,
+        };
+        if let Some((rank, bits)) = bits {
+            keep(&mut self.bit_depth, rank, bits);
+            return true;
+        }
+
+        let audio = match tag {
+            "eac3" | "e-ac3" | "eac-3" | "e-ac-3" => Some((0, "EAC3")),
+            _ if tag.starts_with("ddp") || (tag == "dd" && plus_follows) => Some((0, "EAC3")),
+            "ac3" | "ac-3" => Some((1, "AC3")),
+            _ if tag.starts_with("aac") => Some((2, "AAC")),
+            _ if tag.starts_with("flac") => Some((3, "FLAC")),
+            "opus" => Some((4, "Opus")),
+            "truehd" => Some((5, "TrueHD")),
+            "dts" | "dts-hd" | "dtshd" => Some((6, "DTS")),
+            "mp3" => Some((7, "MP3")),
+            _ => None,
+        };
+        if let Some((rank, codec)) = audio {
+            keep(&mut self.audio_codec, rank, codec);
+            return true;
+        }
+
+        let source = match tag {
+            "bd" | "bdrip" | "bdremux" | "bdmv" | "bluray" | "blu-ray" => Some((0, "BD")),
+            "web" | "webdl" | "web-dl" | "webrip" | "web-rip" => Some((1, "WEB")),
+            "dvd" | "dvdrip" | "dvd-rip" => Some((2, "DVD")),
+            "hdtv" | "hdtvrip" | "hdtv-rip" => Some((3, "TV")),
+            _ => None,
+        };
+        if let Some((rank, source)) = source {
+            keep(&mut self.source, rank, source);
+            return true;
+        }
+
+        // "05v2", "E05v2" or a standalone "v2"
+        if let Some((head, digits)) = tag.rsplit_once('v') {
+            if is_number(digits, 2)
+                && (head.is_empty() || head.ends_with(|c: char| c.is_ascii_digit()))
+            {
+                if self.version.is_none() {
+                    self.version = digits.parse().ok();
+                }
+                return head.is_empty();
+            }
+        }
+
+        if tag == "batch" {
+            self.is_batch = true;
+            return true;
+        }
+
+        matches!(tag, "dl" | "rip") || is_crc32(tag)
+    }
+}
+
+/// `1080p`/`1080i` (rank 0), `1920x1080` (rank 1), `4K` (rank 2) → (rank, height)
+fn resolution_height(tag: &str) -> Option<(u8, &str)> {
+    let dimension = |s: &str| (3..=4).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit());
+
+    if let Some(height) = tag.strip_suffix(['p', 'i']).filter(|h| dimension(h)) {
+        return Some((0, height));
+    }
+    if let Some((width, height)) = tag.split_once('x') {
+        if dimension(width) && dimension(height) {
+            return Some((1, height));
+        }
+    }
+    (tag == "4k").then_some((2, "2160"))
+}
+
+/// `ABCD1234`
+fn is_crc32(s: &str) -> bool {
+    s.len() == 8 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Release metadata (group, resolution, codecs, source, checksum, version,
+/// year, batch) from a suffix-free title's tokens
+fn release_info(tokens: &[Token], file_extension: Option<String>) -> ParsedTitle {
+    let mut tags = ReleaseTags::default();
+    tags.scan(tokens);
+
+    let mut parsed = ParsedTitle {
+        resolution: tags.resolution.map(|(_, resolution)| resolution),
+        video_codec: tags.video_codec.map(|(_, codec)| codec.to_string()),
+        bit_depth: tags.bit_depth.map(|(_, bits)| bits),
+        audio_codec: tags.audio_codec.map(|(_, codec)| codec.to_string()),
+        source: tags.source.map(|(_, source)| source.to_string()),
+        crc32: tags.crc32,
+        version: tags.version,
+        year: tags.year,
+        is_batch: tags.is_batch,
+        is_remake: tags.is_remake,
+        file_extension,
+        ..Default::default()
+    };
+
+    // "[Group] Title ..." (fansub)
+    let fansub_group = tokens
+        .first()
+        .filter(|t| t.kind == TokenKind::Enclosed && t.bracket == Some('['))
+        .map(|t| t.text.trim())
+        .filter(|group| !group.is_empty() && !is_release_tag(group));
+
+    // "Title.S01E01.1080p.WEB-GROUP" (scene), only when there are tags to go by
+    let has_tags = parsed.resolution.is_some() || parsed.video_codec.is_some();
+    let scene_group = tokens
+        .last()
+        .filter(|t| has_tags && t.kind == TokenKind::Word)
+        .and_then(|t| t.text.rsplit_once('-'))
+        .map(|(_, group)| group)
+        .filter(|group| {
+            group.len() >= 2
+                && group.starts_with(|c: char| c.is_ascii_alphabetic())
+                && group.bytes().all(|b| b.is_ascii_alphanumeric())
+                && !is_release_tag(group)
+        });
+
+    parsed.release_group = fansub_group.or(scene_group).map(String::from);
+    parsed
+}
+
+/// Whether a word is a technical tag (`1080p`, `HEVC`, `WEB-DL`, `v2`)
+fn is_tag_word(word: &str) -> bool {
+    ReleaseTags::default().scan_word(word, false)
+}
+
+/// Whether a bracketed token is a technical tag rather than a group name
+fn is_release_tag(text: &str) -> bool {
+    let tokens = title_tokenizer::tokenize(text);
+    let mut words = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Word)
+        .peekable();
+    words.peek().is_some() && words.all(|t| is_tag_word(t.text))
 }
 
 /// Remove common media player suffixes from window title
@@ -112,103 +1147,491 @@ fn remove_player_suffix(title: &str) -> String {
         " - PotPlayer",
         " – VLC media player", // en-dash variant
     ];
+    let user_suffixes = parsing_rules::player_suffixes();
 
     let mut result = title.to_string();
-    for suffix in &suffixes {
+    for suffix in suffixes
+        .iter()
+        .copied()
+        .chain(user_suffixes.iter().map(String::as_str))
+    {
         if let Some(pos) = result.to_lowercase().rfind(&suffix.to_lowercase()) {
-            result = result[..pos].to_string();
+            result = result.get(..pos).unwrap_or(&result).to_string();
             break;
         }
     }
     result.trim().to_string()
 }
 
-/// Try to parse S##E## format (e.g., "Anime S02E05")
-fn try_parse_season_episode(title: &str) -> Option<ParsedTitle> {
-    let re = Regex::new(r"(?i)(.+?)\s*[Ss](\d{1,2})\s*[Ee](\d{1,3})").ok()?;
-    let caps = re.captures(title)?;
+/// Whether `s` is 1..=`max` ASCII digits
+fn is_number(s: &str, max: usize) -> bool {
+    (1..=max).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Split `s` after its leading digits, if there are 1..=`max` of them
+fn take_number(s: &str, max: usize) -> Option<(&str, &str)> {
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+    if (1..=max).contains(&len) {
+        Some(s.split_at(len))
+    } else {
+        None
+    }
+}
+
+/// Strip an ASCII prefix, ignoring case
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Strip a leading `v2`-style release version
+fn strip_version(s: &str) -> &str {
+    strip_prefix_ci(s, "v")
+        .and_then(|rest| take_number(rest, 2))
+        .map_or(s, |(_, rest)| rest)
+}
+
+/// `05`, `05v2` or `12.5` at the start of `s`: the number and what follows it
+fn take_episode(s: &str) -> Option<(&str, &str)> {
+    take_long_episode(s, 3)
+}
+
+/// Like `take_episode`, with up to `max` digits before the decimal point
+fn take_long_episode(s: &str, max: usize) -> Option<(&str, &str)> {
+    let (whole, rest) = take_number(s, max)?;
+    let len = match rest.strip_prefix('.').and_then(|r| take_number(r, 1)) {
+        Some((fraction, _)) => whole.len() + 1 + fraction.len(),
+        None => whole.len(),
+    };
+    let (number, rest) = s.split_at(len);
+    Some((number, strip_version(rest)))
+}
+
+/// The end of a range inside a word: `-04`, `~04`, `-E04`, `E04`
+fn range_end(rest: &str) -> Option<&str> {
+    let r = rest.strip_prefix(['-', '~']).unwrap_or(rest);
+    let r = strip_prefix_ci(r, "e").unwrap_or(r);
+    if r.len() == rest.len() {
+        return None;
+    }
+    let (end, tail) = take_number(r, 3)?;
+    if strip_version(tail).is_empty() {
+        Some(end)
+    } else {
+        None
+    }
+}
+
+/// The end of a range written across a delimiter: `E01+E02`, `S01E01&E02`
+fn range_after<'a>(tokens: &[Token<'a>], i: usize) -> Option<&'a str> {
+    tokens
+        .get(i + 1)
+        .filter(|t| t.kind == TokenKind::Delimiter && matches!(t.text, "-" | "+" | "&" | "~"))?;
+    let word = word_at(tokens, i + 2)?;
+    let word = strip_prefix_ci(word, "ep")
+        .or_else(|| strip_prefix_ci(word, "e"))
+        .unwrap_or(word);
+    match take_number(word, 3)? {
+        (end, "") => Some(end),
+        _ => None,
+    }
+}
+
+/// `E05`, `E05v2`, `E01-E02`: the episode and the end of a range
+fn parse_e_word(word: &str) -> Option<(&str, Option<&str>)> {
+    let rest = strip_prefix_ci(word, "e")?;
+    let (episode, rest) = take_episode(rest)?;
+    Some((episode, range_end(rest)))
+}
+
+fn is_dash(token: &Token) -> bool {
+    token.kind == TokenKind::Delimiter && token.text.contains('-')
+}
+
+/// The text of the word at `i`
+fn word_at<'a>(tokens: &[Token<'a>], i: usize) -> Option<&'a str> {
+    tokens
+        .get(i)
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| t.text)
+}
+
+/// Whether the tokens from `i` on can follow an episode number: nothing,
+/// brackets, release tags or " - Episode Title"
+fn ends_episode(tokens: &[Token], i: usize) -> bool {
+    let token = match tokens.get(i) {
+        Some(token) if token.kind == TokenKind::Delimiter => token,
+        Some(token) => return token.kind == TokenKind::Enclosed,
+        None => return true,
+    };
+    match tokens.get(i + 1) {
+        None => true,
+        Some(next) if next.kind == TokenKind::Enclosed => true,
+        Some(next) => is_tag_word(next.text) || (is_dash(token) && token.text.trim() != token.text),
+    }
+}
+
+/// `S02E05`, `S01E01+E02`, `S01E03-04`, `S01 E05`
+fn find_season_episode(tokens: &[Token]) -> Option<ParsedTitle> {
+    (1..tokens.len()).find_map(|i| {
+        let word = word_at(tokens, i)?;
+        let season_rest = strip_prefix_ci(word, "s")?;
+        let (season, rest) = take_number(season_rest, 2)?;
+
+        let (episode, end, last) = if rest.is_empty() {
+            // "S01 E05"
+            let (j, next) = title_tokenizer::next_word(tokens, i)?;
+            let (episode, end) = parse_e_word(next)?;
+            (episode, end, j)
+        } else {
+            let (episode, end) = parse_e_word(rest)?;
+            (episode, end, i)
+        };
+        if episode.contains('.') {
+            return None;
+        }
+
+        let mut parsed = ParsedTitle {
+            title: title_from_tokens(&tokens[..i]),
+            season: season.parse().ok(),
+            ..Default::default()
+        };
+        set_episodes(
+            &mut parsed,
+            episode,
+            end.or_else(|| range_after(tokens, last)),
+        )?;
+        Some(parsed)
+    })
+}
+
+/// `Episode 5`, `Ep. 05`, `Ep05`, `E05`, `Episodes 1-3`, `E01+E02`
+fn find_episode_keyword(tokens: &[Token]) -> Option<ParsedTitle> {
+    (1..tokens.len()).find_map(|i| {
+        let word = word_at(tokens, i)?;
+        let keyword = ["episodes", "episode", "ep"]
+            .iter()
+            .find_map(|k| strip_prefix_ci(word, k));
 
-    let anime_title = clean_title(caps.get(1)?.as_str());
-    let season: i32 = caps.get(2)?.as_str().parse().ok()?;
-    let episode: i32 = caps.get(3)?.as_str().parse().ok()?;
+        let (episode, end, last) = match keyword {
+            // "Episode 25", "Ep. 5"; long runners reach four digits ("Episode 1071")
+            Some("") => {
+                let (j, next) = title_tokenizer::next_word(tokens, i)?;
+                let (episode, rest) = take_long_episode(next, 4)?;
+                (episode, range_end(rest), j)
+            }
+            // "Ep05"
+            Some(rest) => {
+                let (episode, rest) = take_episode(rest)?;
+                (episode, range_end(rest), i)
+            }
+            None => {
+                let (episode, end) = parse_e_word(word)?;
+                (episode, end, i)
+            }
+        };
 
-    Some(ParsedTitle {
-        title: Some(anime_title),
-        episode: Some(episode),
-        season: Some(season),
+        let mut parsed = ParsedTitle {
+            title: title_from_tokens(&tokens[..i]),
+            ..Default::default()
+        };
+        set_episodes(
+            &mut parsed,
+            episode,
+            end.or_else(|| range_after(tokens, last)),
+        )?;
+        Some(parsed)
     })
 }
 
-/// Try to parse "Episode ##" or "Ep ##" format
-fn try_parse_episode_keyword(title: &str) -> Option<ParsedTitle> {
-    let re = Regex::new(r"(?i)(.+?)\s*(?:Episode|Ep\.?)\s*(\d{1,3})").ok()?;
-    let caps = re.captures(title)?;
+/// Japanese/Chinese episode markers: `第05話`, `第5话`, `05話`, `第12集`
+fn find_native_episode(tokens: &[Token]) -> Option<ParsedTitle> {
+    tokens.iter().enumerate().find_map(|(i, token)| {
+        if token.kind == TokenKind::Delimiter {
+            return None;
+        }
+        let caps = NATIVE_EPISODE.captures(token.text)?;
+        let marker = caps.get(0)?;
+        let number = caps.get(1)?.as_str();
 
-    let anime_title = clean_title(caps.get(1)?.as_str());
-    let episode: i32 = caps.get(2)?.as_str().parse().ok()?;
+        // "葬送のフリーレン第05話": the title can be glued to the marker
+        let glued = &token.text[..marker.start()];
+        let title = if token.kind == TokenKind::Word && !glued.trim().is_empty() {
+            let mut prefix = tokens[..i].to_vec();
+            prefix.push(Token {
+                text: glued,
+                ..*token
+            });
+            title_from_tokens(&prefix)
+        } else {
+            title_from_tokens(&tokens[..i])
+        };
 
-    Some(ParsedTitle {
-        title: Some(anime_title),
-        episode: Some(episode),
-        season: None,
+        let mut parsed = ParsedTitle {
+            title,
+            ..Default::default()
+        };
+        match parse_native_number(number) {
+            Some(n) if !number.contains('.') => parsed.episode = Some(n),
+            _ => set_episodes(&mut parsed, number, None)?,
+        }
+        Some(parsed)
     })
 }
 
-/// Try to parse "Anime - ## " format (common in fansubs)
-fn try_parse_dash_number(title: &str) -> Option<ParsedTitle> {
-    // Match: Title - 05 [quality] or Title - 05.mkv
-    let re = Regex::new(r"(.+?)\s*-\s*(\d{1,3})(?:\s*[\[\(]|\s*\.|\s*$)").ok()?;
-    let caps = re.captures(title)?;
+/// `Title - 05`, `Title - 05v2 [1080p]`, `Title - 12.5`, `Title - 01-12`,
+/// `Title - 05 - Episode Title` (fansub style)
+fn find_dash_number(tokens: &[Token]) -> Option<ParsedTitle> {
+    (1..tokens.len()).find_map(|i| {
+        if !is_dash(&tokens[i]) {
+            return None;
+        }
+        let (episode, rest) = take_episode(word_at(tokens, i + 1)?)?;
 
-    let anime_title = clean_title(caps.get(1)?.as_str());
-    let episode: i32 = caps.get(2)?.as_str().parse().ok()?;
+        let (end, last) = if !rest.is_empty() {
+            // "01-12"
+            (Some(range_end(rest)?), i + 1)
+        } else {
+            // "01 ~ 12"
+            let spaced = tokens
+                .get(i + 2)
+                .filter(|t| t.kind == TokenKind::Delimiter && t.text.contains(['-', '~']))
+                .and_then(|_| word_at(tokens, i + 3))
+                .and_then(|w| take_number(w, 3))
+                .filter(|(_, tail)| strip_version(tail).is_empty());
+            match spaced {
+                Some((end, _)) => (Some(end), i + 3),
+                None => (None, i + 1),
+            }
+        };
+        if !ends_episode(tokens, last + 1) {
+            return None;
+        }
 
-    Some(ParsedTitle {
-        title: Some(anime_title),
-        episode: Some(episode),
-        season: None,
+        let mut parsed = ParsedTitle {
+            title: title_from_tokens(&tokens[..i]),
+            ..Default::default()
+        };
+        set_episodes(&mut parsed, episode, end)?;
+        Some(parsed)
     })
 }
 
-/// Try to parse [SubGroup] Title - ## [quality] format
-fn try_parse_bracketed(title: &str) -> Option<ParsedTitle> {
-    // First, remove leading [SubGroup] tag
-    let re_subgroup = Regex::new(r"^\s*\[[^\]]+\]\s*").ok()?;
-    let without_subgroup = re_subgroup.replace(title, "").to_string();
+/// A bare bracketed episode: `[Group][Title][05][1080P]`, `【推しの子】[05]`
+fn find_bracketed_number(tokens: &[Token]) -> Option<ParsedTitle> {
+    tokens.iter().enumerate().find_map(|(i, token)| {
+        if token.kind != TokenKind::Enclosed || token.bracket != Some('[') {
+            return None;
+        }
+        // "[ 01 ~ 12 ]"
+        let content: String = token.text.split_whitespace().collect();
+        let (episode, rest) = take_episode(&content)?;
+        let end = if rest.is_empty() {
+            None
+        } else {
+            Some(range_end(rest)?)
+        };
 
-    // Now try dash-number parsing on the cleaned string
-    try_parse_dash_number(&without_subgroup)
+        let mut parsed = ParsedTitle {
+            title: Some(title_from_tokens(&tokens[..i])?),
+            ..Default::default()
+        };
+        set_episodes(&mut parsed, episode, end)?;
+        Some(parsed)
+    })
 }
 
-/// Clean up a title string by removing common noise
-fn clean_title(title: &str) -> String {
-    let mut result = title.to_string();
+/// A two-digit or zero-padded number after the title, followed by nothing
+/// but tags: `Anime_Title_01.mkv`, `[Group] Title 05 [1080p].mkv`
+///
+/// "Mob Psycho 100" and "Kaiju No 8" keep their numbers.
+fn find_trailing_number(tokens: &[Token]) -> Option<ParsedTitle> {
+    (1..tokens.len()).find_map(|i| {
+        let word = word_at(tokens, i)?;
+        if is_dash(&tokens[i - 1]) || tokens[i - 1].kind != TokenKind::Delimiter {
+            return None;
+        }
+        let (episode, rest) = take_episode(word)?;
+        let whole = episode.split('.').next()?;
+        if !rest.is_empty()
+            || !(whole.len() == 2 || whole.starts_with('0'))
+            || whole.bytes().all(|b| b == b'0')
+        {
+            return None;
+        }
+
+        let title_words = tokens[..i].iter().any(|t| t.kind == TokenKind::Word);
+        let only_tags_follow = tokens[i + 1..]
+            .iter()
+            .all(|t| t.kind != TokenKind::Word || is_tag_word(t.text));
+        if !title_words || !only_tags_follow {
+            return None;
+        }
+
+        let mut parsed = ParsedTitle {
+            title: title_from_tokens(&tokens[..i]),
+            ..Default::default()
+        };
+        set_episodes(&mut parsed, episode, None)?;
+        Some(parsed)
+    })
+}
+
+/// Fold full-width forms (`０５`, `［］`, `（）`) to ASCII with NFKC and map
+/// CJK brackets (`【】`, `〔〕`) to square brackets; `「」` quotes become spaces
+fn normalize_width(title: &str) -> String {
+    title
+        .nfkc()
+        .map(|c| match c {
+            '【' | '〔' | '〖' | '〘' | '〚' => '[',
+            '】' | '〕' | '〗' | '〙' | '〛' => ']',
+            '「' | '」' | '『' | '』' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Parse a number written with digits or simple kanji numerals (一 … 九十九)
+fn parse_native_number(s: &str) -> Option<i32> {
+    if let Ok(n) = s.parse() {
+        return Some(n);
+    }
+
+    let digit = |c: char| {
+        "〇一二三四五六七八九"
+            .chars()
+            .position(|d| d == c)
+            .map(|p| p as i32)
+    };
+    match s.chars().collect::<Vec<_>>().as_slice() {
+        [c] if *c == '十' => Some(10),
+        [c] => digit(*c),
+        ['十', ones] => Some(10 + digit(*ones)?),
+        [tens, '十'] => Some(digit(*tens)? * 10),
+        [tens, '十', ones] => Some(digit(*tens)? * 10 + digit(*ones)?),
+        _ => None,
+    }
+}
+
+/// Pick the title out of the tokens before an episode marker
+///
+/// Plain words win; otherwise the last bracket that isn't a release tag is
+/// used (`[Group][Title][05]`, `【推しの子】 第05話`).
+fn title_from_tokens(tokens: &[Token]) -> Option<String> {
+    let words = title_tokenizer::join_words(tokens);
+    if !words.is_empty() {
+        return Some(words);
+    }
+
+    tokens
+        .iter()
+        .rev()
+        .filter(|t| t.kind == TokenKind::Enclosed)
+        .map(|t| t.text.trim())
+        .find(|text| !text.is_empty() && !is_release_tag(text))
+        .map(String::from)
+}
 
-    // Remove file extensions
-    let extensions = [".mkv", ".mp4", ".avi", ".webm", ".m4v"];
-    for ext in &extensions {
-        if result.to_lowercase().ends_with(ext) {
-            result = result[..result.len() - ext.len()].to_string();
+/// Where the title of a name without an episode marker stops: at the first
+/// release tag after it ("Your.Name.2016.1080p.BluRay" → "Your Name 2016")
+fn title_end(tokens: &[Token]) -> usize {
+    let mut seen_word = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind == TokenKind::Word {
+            if seen_word && is_tag_word(token.text) {
+                return i;
+            }
+            seen_word = true;
         }
     }
+    tokens.len()
+}
 
-    // Remove quality tags like [1080p], (720p), [BD], etc.
-    let quality_re = Regex::new(r"[\[\(]\s*(?:\d{3,4}p|BD|HEVC|x264|x265|AAC|FLAC|10bit|Hi10P|WEB-DL|WEB|BDRip|BluRay)\s*[\]\)]").unwrap();
-    result = quality_re.replace_all(&result, "").to_string();
+/// Move native season markers (`第2期`, `第二季`, `2期`, `シーズン2`) from the title to `season`
+fn extract_native_season(parsed: &mut ParsedTitle) {
+    let title = match parsed.title.as_deref() {
+        Some(t) => t,
+        None => return,
+    };
+    let caps = match NATIVE_SEASON.captures(title) {
+        Some(caps) => caps,
+        None => return,
+    };
 
-    // Remove subgroup tags at the start
-    let subgroup_re = Regex::new(r"^\s*\[[^\]]+\]\s*").unwrap();
-    result = subgroup_re.replace(&result, "").to_string();
+    let number = caps
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .and_then(|m| parse_native_number(m.as_str()));
+    if parsed.season.is_none() {
+        parsed.season = number;
+    }
 
-    // Remove hash tags at the end like [ABCD1234]
-    let hash_re = Regex::new(r"\s*\[[A-Fa-f0-9]{8}\]\s*$").unwrap();
-    result = hash_re.replace(&result, "").to_string();
+    let stripped = NATIVE_SEASON.replace(title, " ").trim().to_string();
+    if !stripped.is_empty() {
+        parsed.title = Some(stripped);
+    }
+}
 
-    // Clean up extra whitespace and dashes
-    result = result.trim().to_string();
-    result = result.trim_end_matches('-').trim().to_string();
+/// A plausible release year: 1940 up to next year ("Blade Runner 2049" keeps its number)
+fn parse_year(s: &str) -> Option<i32> {
+    if s.len() != 4 || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i32 = s.parse().ok()?;
+    (1940..=current_year() + 1).contains(&year).then_some(year)
+}
 
-    result
+fn current_year() -> i32 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Average Gregorian year; only has to be right to the year
+    1970 + (secs / 31_556_952) as i32
+}
+
+/// Move a year and remake marker ending the title ("Fruits Basket 2019",
+/// "Hunter x Hunter (2011)", "Hellsing Remake") to `year` / `is_remake`
+fn extract_year(parsed: &mut ParsedTitle) {
+    let title = match parsed.title.as_deref() {
+        Some(t) => t,
+        None => return,
+    };
+
+    let mut words: Vec<&str> = title.split_whitespace().collect();
+    let mut year = None;
+    let mut is_remake = false;
+    // The first word is always part of the name ("2012 - 05" is a title)
+    while let [_, .., last] = words.as_slice() {
+        let bare = last.trim_matches(['(', ')', '[', ']']);
+        if year.is_none() && parse_year(bare).is_some() {
+            year = parse_year(bare);
+        } else if !is_remake && REMAKE_MARKERS.iter().any(|m| bare.eq_ignore_ascii_case(m)) {
+            is_remake = true;
+        } else {
+            break;
+        }
+        words.pop();
+    }
+
+    if year.is_some() || is_remake {
+        parsed.title = Some(words.join(" "));
+        parsed.year = parsed.year.or(year);
+        parsed.is_remake |= is_remake;
+    }
+}
+
+/// Clean up a free-form title: drop the extension, brackets and anything
+/// from the first release tag on
+fn clean_title(title: &str) -> String {
+    let (body, _) = title_tokenizer::split_extension(title, &FILE_EXTENSIONS);
+    let tokens = title_tokenizer::tokenize(body);
+    title_from_tokens(&tokens[..title_end(&tokens)]).unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -258,17 +1681,494 @@ mod tests {
         // Episode may or may not be detected depending on title format
     }
 
+    #[test]
+    fn test_release_info() {
+        let result = parse_window_title(
+            "[SubsPlease] Frieren - 05v2 (1080p) [HEVC 10bit AAC] [ABCD1234].mkv - VLC media player",
+        );
+        assert_eq!(result.release_group, Some("SubsPlease".to_string()));
+        assert_eq!(result.resolution, Some("1080p".to_string()));
+        assert_eq!(result.video_codec, Some("HEVC".to_string()));
+        assert_eq!(result.bit_depth, Some(10));
+        assert_eq!(result.audio_codec, Some("AAC".to_string()));
+        assert_eq!(result.crc32, Some("ABCD1234".to_string()));
+        assert_eq!(result.version, Some(2));
+        assert_eq!(result.file_extension, Some("mkv".to_string()));
+        assert_eq!(result.source, None);
+    }
+
+    #[test]
+    fn test_scene_release_info() {
+        let result =
+            parse_window_title("Spy.x.Family.S02E03.1080p.WEB-DL.DDP2.0.H.264-VARYG.mkv - mpv");
+        assert_eq!(result.title, Some("Spy x Family".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(3));
+        assert_eq!(result.release_group, Some("VARYG".to_string()));
+        assert_eq!(result.source, Some("WEB".to_string()));
+        assert_eq!(result.video_codec, Some("AVC".to_string()));
+        assert_eq!(result.audio_codec, Some("EAC3".to_string()));
+    }
+
+    #[test]
+    fn test_no_release_info_for_plain_titles() {
+        let result = parse_window_title("Attack on Titan Episode 25 - MPC-HC");
+        assert_eq!(result.release_group, None);
+        assert_eq!(result.resolution, None);
+        assert_eq!(result.crc32, None);
+        assert_eq!(result.version, None);
+        assert_eq!(result.file_extension, None);
+    }
+
+    #[test]
+    fn test_version_tag() {
+        let result = parse_window_title("[SubsPlease] Frieren - 05v2 (1080p).mkv - mpv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.episode, Some(5));
+        assert_eq!(result.version, Some(2));
+    }
+
+    #[test]
+    fn test_decimal_episode() {
+        let result = parse_window_title("[Group] Oshi no Ko - 12.5 [1080p].mkv - VLC media player");
+        assert_eq!(result.title, Some("Oshi no Ko".to_string()));
+        assert_eq!(result.episode, None);
+        assert_eq!(result.episode_decimal, Some(12.5));
+
+        let result = parse_window_title("Oshi.no.Ko.12.5.mkv");
+        assert_eq!(result.episode, None);
+
+        let result = parse_window_title("Frieren.01.1080p.mkv");
+        assert_eq!(result.episode_decimal, None);
+    }
+
+    #[test]
+    fn test_episode_ranges() {
+        let result = parse_window_title("Bocchi the Rock S01E01+E02.mkv - mpv");
+        assert_eq!(result.title, Some("Bocchi the Rock".to_string()));
+        assert_eq!(result.episode, Some(2));
+        assert_eq!(
+            result.episode_range,
+            Some(EpisodeRange { start: 1, end: 2 })
+        );
+
+        let result = parse_window_title("Bocchi the Rock E01+E02.mkv");
+        assert_eq!(result.episode, Some(2));
+        assert_eq!(
+            result.episode_range,
+            Some(EpisodeRange { start: 1, end: 2 })
+        );
+
+        let result = parse_window_title("Bocchi the Rock S01E03-04 1080p.mkv");
+        assert_eq!(result.episode, Some(4));
+
+        // "Episode 25 - 1080p" is not a range
+        let result = parse_window_title("Attack on Titan Episode 25 1080p");
+        assert_eq!(result.episode, Some(25));
+        assert_eq!(result.episode_range, None);
+    }
+
+    #[test]
+    fn test_batch() {
+        let result = parse_window_title("[Judas] Vinland Saga - 01-24 [Batch] [1080p].mkv");
+        assert_eq!(result.title, Some("Vinland Saga".to_string()));
+        assert!(result.is_batch);
+        assert_eq!(result.episode, None);
+        assert_eq!(
+            result.episode_range,
+            Some(EpisodeRange { start: 1, end: 24 })
+        );
+    }
+
+    #[test]
+    fn test_media_kinds() {
+        let cases = [
+            (
+                "[Group] Made in Abyss - OVA 2 [1080p].mkv",
+                MediaKind::Ova,
+                Some(2),
+            ),
+            ("Kaguya-sama SP03.mkv", MediaKind::Special, Some(3)),
+            ("Frieren S00E02.mkv", MediaKind::Special, Some(2)),
+            ("Chainsaw Man - Special.mkv", MediaKind::Special, Some(1)),
+            (
+                "[SubsPlease] Frieren - NCOP1 [1080p] [ABCD1234].mkv",
+                MediaKind::Opening,
+                None,
+            ),
+            ("Frieren - NCED [1080p].mkv", MediaKind::Ending, None),
+            ("Frieren - PV2.mkv", MediaKind::Preview, None),
+            (
+                "Violet Evergarden The Movie [1080p].mkv",
+                MediaKind::Movie,
+                Some(1),
+            ),
+            ("Frieren - 05 [1080p].mkv", MediaKind::Episode, Some(5)),
+        ];
+
+        for (input, kind, episode) in cases {
+            let result = parse_window_title(input);
+            assert_eq!(result.kind, kind, "{}", input);
+            assert_eq!(result.episode, episode, "{}", input);
+        }
+
+        let result = parse_window_title("[Group] Made in Abyss - OVA 2 [1080p].mkv");
+        assert_eq!(result.title, Some("Made in Abyss".to_string()));
+        let result = parse_window_title("Violet Evergarden The Movie [1080p].mkv");
+        assert_eq!(result.title, Some("Violet Evergarden".to_string()));
+        assert!(!MediaKind::Opening.is_countable());
+    }
+
+    #[test]
+    fn test_kind_marker_at_title_start_is_part_of_name() {
+        let result = parse_window_title("Special A - 05.mkv - VLC media player");
+        assert_eq!(result.kind, MediaKind::Episode);
+        assert_eq!(result.title, Some("Special A".to_string()));
+        assert_eq!(result.episode, Some(5));
+    }
+
+    #[test]
+    fn test_native_episode_and_season() {
+        let result = parse_window_title("葬送のフリーレン 第2期 第05話.mkv - mpv");
+        assert_eq!(result.title, Some("葬送のフリーレン".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_window_title("葬送的芙莉莲 第二季 第十二集.mp4");
+        assert_eq!(result.title, Some("葬送的芙莉莲".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(12));
+    }
+
+    #[test]
+    fn test_full_width_and_cjk_brackets() {
+        let result =
+            parse_window_title("【推しの子】 第０５話 （１０８０ｐ）.mkv - VLC media player");
+        assert_eq!(result.title, Some("推しの子".to_string()));
+        assert_eq!(result.episode, Some(5));
+        assert_eq!(result.resolution, Some("1080p".to_string()));
+
+        let result = parse_window_title("[桜都字幕组][葬送的芙莉莲][05][1080P][简体内嵌].mp4");
+        assert_eq!(result.title, Some("葬送的芙莉莲".to_string()));
+        assert_eq!(result.episode, Some(5));
+        assert_eq!(result.release_group, Some("桜都字幕组".to_string()));
+
+        let result = parse_window_title(
+            "[Lilith-Raws] 葬送的芙莉莲 - 05 [Baha][WEB-DL][1080p][AVC AAC].mp4",
+        );
+        assert_eq!(result.title, Some("葬送的芙莉莲".to_string()));
+        assert_eq!(result.episode, Some(5));
+    }
+
+    #[test]
+    fn test_media_path_season_folder() {
+        let result = parse_media_path("/Anime/Mushoku Tensei/Season 2/05.mkv");
+        assert_eq!(result.title, Some("Mushoku Tensei".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_media_path(r"D:\Anime\[Judas] Mushoku Tensei S2 [BD 1080p]\E05v2.mkv");
+        assert_eq!(result.title, Some("Mushoku Tensei".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(5));
+        assert_eq!(result.version, Some(2));
+        assert_eq!(result.release_group, Some("Judas".to_string()));
+        assert_eq!(result.resolution, Some("1080p".to_string()));
+    }
+
+    #[test]
+    fn test_media_path_file_name_wins() {
+        let result =
+            parse_media_path("/home/me/Anime/Frieren/[SubsPlease] Frieren - 05 (1080p).mkv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_media_path("/Anime/86/86 - 05.mkv");
+        assert_eq!(result.title, Some("86".to_string()));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_media_path("/Anime/Frieren/05 - The Hero's Journey.mkv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.episode, Some(5));
+    }
+
+    #[test]
+    fn test_media_path_specials_and_generic_folders() {
+        let result = parse_media_path("/Anime/Frieren/Specials/02.mkv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.kind, MediaKind::Special);
+        assert_eq!(result.episode, Some(2));
+
+        let result = parse_media_path("/home/me/Downloads/07.mkv");
+        assert_eq!(result.title, None);
+        assert_eq!(result.episode, Some(7));
+
+        let result = parse_media_path("/Anime/葬送のフリーレン/第2期/第05話.mkv");
+        assert_eq!(result.title, Some("葬送のフリーレン".to_string()));
+        assert_eq!(result.season, Some(2));
+        assert_eq!(result.episode, Some(5));
+    }
+
+    #[test]
+    fn test_confidence_ranking() {
+        let result = parse_window_title_ranked("Anime Title S02E05.mkv - mpv");
+        assert_eq!(result.best.strategy, ParseStrategy::SeasonEpisode);
+        assert!(result.best.confidence >= 0.9);
+        assert!(result
+            .alternatives
+            .windows(2)
+            .all(|pair| pair[0].confidence >= pair[1].confidence));
+        assert!(result
+            .alternatives
+            .iter()
+            .all(|alt| alt.parsed != result.best.parsed));
+
+        // Two readings: the keyword wins, the dash number is kept as an alternative
+        let result = parse_window_title_ranked("Anime Title Ep 3 Part 2 - 07.mkv");
+        assert_eq!(result.best.parsed.episode, Some(3));
+        assert!(result
+            .alternatives
+            .iter()
+            .any(|alt| alt.parsed.episode == Some(7)));
+    }
+
+    #[test]
+    fn test_low_confidence_guess() {
+        let result = parse_window_title_ranked("Some Random Video - VLC media player");
+        assert_eq!(result.best.strategy, ParseStrategy::Fallback);
+        assert!(result.best.confidence < MIN_CONFIDENCE);
+
+        // An explicit marker explains the missing episode
+        let result = parse_window_title_ranked("Kimi no Na wa The Movie.mkv");
+        assert_eq!(result.best.parsed.kind, MediaKind::Movie);
+        assert!(result.best.confidence >= MIN_CONFIDENCE);
+
+        // A bare number is only trusted once a folder names the series
+        let result = parse_media_path_ranked("/home/me/Downloads/07.mkv");
+        assert!(result.best.confidence < MIN_CONFIDENCE);
+        let result = parse_media_path_ranked("/Anime/Frieren/07.mkv");
+        assert_eq!(result.best.strategy, ParseStrategy::BareNumber);
+        assert!(result.best.confidence >= MIN_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_streaming_site_titles() {
+        // (tab title, site, title, season, episode)
+        let fixtures = [
+            (
+                "Chitose Is In The Ramune Bottle Episode 1 English Sub at Hianime - Google Chrome",
+                "hianime",
+                "Chitose Is In The Ramune Bottle",
+                None,
+                Some(1),
+            ),
+            (
+                "Watch Frieren Episode 5 English Sub at HiAnime - Google Chrome – Suraj",
+                "hianime",
+                "Frieren",
+                None,
+                Some(5),
+            ),
+            (
+                "Watch Mushoku Tensei Season 2 Episode 12 English Sub/Dub online Free on HiAnime.to - Mozilla Firefox",
+                "hianime",
+                "Mushoku Tensei",
+                Some(2),
+                Some(12),
+            ),
+            (
+                "Crunchyroll - Watch Frieren: Beyond Journey's End Episode 5 - Google Chrome",
+                "crunchyroll",
+                "Frieren: Beyond Journey's End",
+                None,
+                Some(5),
+            ),
+            (
+                "Frieren: Beyond Journey's End E5 - Phantoms of the Dead - Watch on Crunchyroll — Mozilla Firefox",
+                "crunchyroll",
+                "Frieren: Beyond Journey's End",
+                None,
+                Some(5),
+            ),
+            (
+                "Watch Oshi no Ko Season 2 Episode 3 | HIDIVE - Brave",
+                "hidive",
+                "Oshi no Ko",
+                Some(2),
+                Some(3),
+            ),
+            (
+                "Cyberpunk: Edgerunners S1:E4 Lucky You | Netflix - Google Chrome",
+                "netflix",
+                "Cyberpunk: Edgerunners",
+                Some(1),
+                Some(4),
+            ),
+            (
+                "Prime Video: Vinland Saga - Season 2 - Microsoft\u{200b} Edge",
+                "prime-video",
+                "Vinland Saga",
+                Some(2),
+                None,
+            ),
+            (
+                "Watch Dandadan Episode 7 English Subbed at Anitaku - Opera",
+                "gogoanime",
+                "Dandadan",
+                None,
+                Some(7),
+            ),
+            (
+                "Watch Kaiju No. 8 Episode 10 English Sub/Dub online Free on 9anime - Vivaldi",
+                "9anime",
+                "Kaiju No. 8",
+                None,
+                Some(10),
+            ),
+            (
+                "Sousou no Frieren Ep. 5 :: animepahe - Google Chrome",
+                "animepahe",
+                "Sousou no Frieren",
+                None,
+                Some(5),
+            ),
+            (
+                "(2) Blue Lock Episode 3 [English Sub] - YouTube - Google Chrome",
+                "youtube",
+                "Blue Lock",
+                None,
+                Some(3),
+            ),
+        ];
+
+        for (tab, site, title, season, episode) in fixtures {
+            let result = parse_window_title(tab);
+            assert_eq!(result.site.as_deref(), Some(site), "{}", tab);
+            assert_eq!(result.title.as_deref(), Some(title), "{}", tab);
+            assert_eq!(result.season, season, "{}", tab);
+            assert_eq!(result.episode, episode, "{}", tab);
+        }
+    }
+
+    #[test]
+    fn test_site_series_page_is_low_confidence() {
+        let result =
+            parse_window_title_ranked("Watch Frieren | Netflix Official Site - Google Chrome");
+        assert_eq!(result.best.parsed.title, Some("Frieren".to_string()));
+        assert_eq!(result.best.parsed.episode, None);
+        assert!(result.best.confidence < MIN_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_user_parsing_rules() {
+        let _rules = parsing_rules::ScopedRules::install(
+            parsing_rules::parse_rules(
+                r#"{
+                    "player_suffixes": [" - Kodi"],
+                    "patterns": ["^(?P<title>.+?) Folge (?P<episode>\\d+)"],
+                    "title_rewrites": { "Kusuriya no Hitorigoto": "The Apothecary Diaries" }
+                }"#,
+            )
+            .unwrap(),
+        );
+
+        let result = parse_window_title_ranked("Spy x Family Folge 7 - Kodi");
+        assert_eq!(result.best.strategy, ParseStrategy::UserPattern);
+        assert_eq!(result.best.parsed.title, Some("Spy x Family".to_string()));
+        assert_eq!(result.best.parsed.episode, Some(7));
+
+        let result = parse_window_title("Kusuriya no Hitorigoto - 03 - Kodi");
+        assert_eq!(result.title, Some("The Apothecary Diaries".to_string()));
+        assert_eq!(result.episode, Some(3));
+    }
+
     #[test]
     fn test_removes_player_suffix() {
         let result = remove_player_suffix("Anime - 01 - VLC media player");
         assert_eq!(result, "Anime - 01");
     }
-}
 
     #[test]
-    fn test_hianime_browser_title() {
-        let result = parse_window_title("Chitose Is In The Ramune Bottle Episode 1 English Sub at Hianime - Google Chrome");
-        println!("Parsed: title={:?}, episode={:?}", result.title, result.episode);
-        assert_eq!(result.episode, Some(1));
-        assert!(result.title.is_some());
+    fn test_trailing_number() {
+        let result = parse_window_title_ranked("Anime_Title_01.mkv");
+        assert_eq!(result.best.strategy, ParseStrategy::TrailingNumber);
+        assert_eq!(result.best.parsed.title, Some("Anime Title".to_string()));
+        assert_eq!(result.best.parsed.episode, Some(1));
+
+        let result = parse_window_title("[Group] Frieren 05 [1080p].mkv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.episode, Some(5));
+
+        // Numbers that belong to the name stay there
+        let result = parse_window_title("Mob Psycho 100.mkv");
+        assert_eq!(result.title, Some("Mob Psycho 100".to_string()));
+        assert_eq!(result.episode, None);
+    }
+
+    #[test]
+    fn test_dash_number_with_episode_title() {
+        let result = parse_window_title("Frieren - 05 - The Hero's Journey.mkv");
+        assert_eq!(result.title, Some("Frieren".to_string()));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_window_title("One Piece Episode 1071 - mpv");
+        assert_eq!(result.title, Some("One Piece".to_string()));
+        assert_eq!(result.episode, Some(1071));
+    }
+
+    #[test]
+    fn test_year_and_remake() {
+        let result = parse_window_title("Hunter x Hunter (2011) - 05 [1080p].mkv");
+        assert_eq!(result.title, Some("Hunter x Hunter".to_string()));
+        assert_eq!(result.year, Some(2011));
+        assert_eq!(result.episode, Some(5));
+
+        let result = parse_window_title("[SubsPlease] Fruits Basket 2019 - 12 (720p).mkv");
+        assert_eq!(result.title, Some("Fruits Basket".to_string()));
+        assert_eq!(result.year, Some(2019));
+        assert_eq!(result.episode, Some(12));
+
+        let result = parse_window_title("Your.Name.2016.1080p.BluRay.x264.mkv");
+        assert_eq!(result.title, Some("Your Name".to_string()));
+        assert_eq!(result.year, Some(2016));
+
+        let result = parse_window_title("Hellsing Remake - 03.mkv");
+        assert_eq!(result.title, Some("Hellsing".to_string()));
+        assert!(result.is_remake);
+
+        // Numbers that can't be release years stay in the name
+        let result = parse_window_title("Blade Runner 2049 - 01.mkv");
+        assert_eq!(result.title, Some("Blade Runner 2049".to_string()));
+        assert_eq!(result.year, None);
+
+        let result = parse_media_path("/Anime/Hunter x Hunter (2011)/Season 1/05.mkv");
+        assert_eq!(result.title, Some("Hunter x Hunter".to_string()));
+        assert_eq!(result.year, Some(2011));
+        assert_eq!(result.episode, Some(5));
     }
+
+    /// Time per title; run with
+    /// `cargo test --release bench_parse_window_title -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_parse_window_title() {
+        let titles = [
+            "[SubsPlease] Jujutsu Kaisen - 23 (1080p) [ABCD1234].mkv - mpv",
+            "Spy.x.Family.S02E03.1080p.WEB-DL.DDP2.0.H.264-VARYG.mkv - VLC media player",
+            "[Erai-raws] Oshi no Ko - 12.5 [1080p][Multiple Subtitle].mkv",
+            "[字幕组][葬送的芙莉莲][05][1080P].mp4",
+            "Kusuriya no Hitorigoto Episode 12 - MPC-HC",
+            "[Group] Frieren - 01-28 [BD 1080p HEVC FLAC] [Batch]",
+        ];
+        let rounds = 2000;
+
+        let start = std::time::Instant::now();
+        for _ in 0..rounds {
+            for title in titles {
+                std::hint::black_box(parse_window_title(std::hint::black_box(title)));
+            }
+        }
+        let per_title = start.elapsed() / (rounds * titles.len() as u32);
+        println!("parse_window_title: {:?} per title", per_title);
+    }
+}
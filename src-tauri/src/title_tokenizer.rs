@@ -0,0 +1,288 @@
+//! Title Tokenizer Module
+//!
+//! PURPOSE: Split a window title or file name into tokens in a single pass,
+//! so `title_parser` and `manga_parser` can classify words instead of
+//! running a regex per strategy on every poll
+//!
+//! APPROACH: Modeled on anitopy. A bracketed group becomes one `Enclosed`
+//! token, a run of delimiters (spaces, `_`, `.`, `-`, `+`, ...) one
+//! `Delimiter` token, and everything else `Word` tokens. A hyphen between
+//! letters or digits stays in its word ("Kaguya-sama", "WEB-DL", "01-24"),
+//! as do decimal points ("12.5") and the dot in "H.264". Tokens borrow from
+//! the input, so the token list is the only allocation.
+
+/// What a token is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Text between delimiters
+    Word,
+    /// Contents of a `[...]`, `(...)` or `{...}` group
+    Enclosed,
+    /// A run of delimiter characters
+    Delimiter,
+}
+
+/// One piece of the tokenized text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    /// Token text; an `Enclosed` token's brackets are not included
+    pub text: &'a str,
+    /// Opening bracket of an `Enclosed` token
+    pub bracket: Option<char>,
+}
+
+/// Characters that separate words, besides whitespace
+const DELIMITERS: [char; 7] = ['_', '.', '-', '+', '&', '~', '|'];
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || DELIMITERS.contains(&c)
+}
+
+fn is_bracket(c: char) -> bool {
+    matches!(c, '[' | ']' | '(' | ')' | '{' | '}')
+}
+
+fn closing_bracket(open: char) -> Option<char> {
+    match open {
+        '[' => Some(']'),
+        '(' => Some(')'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// Split text into words, bracketed groups and delimiter runs
+///
+/// Unmatched brackets are treated as delimiters.
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    // Kind and start of the word or delimiter run being read
+    let mut run: Option<(TokenKind, usize)> = None;
+    let mut skip_to = 0;
+
+    for (i, c) in text.char_indices() {
+        if i < skip_to {
+            continue;
+        }
+
+        if let Some(close) = closing_bracket(c) {
+            if let Some(len) = text[i + 1..].find(close) {
+                push_run(&mut tokens, text, run.take(), i);
+                tokens.push(Token {
+                    kind: TokenKind::Enclosed,
+                    text: &text[i + 1..i + 1 + len],
+                    bracket: Some(c),
+                });
+                skip_to = i + len + 2;
+                continue;
+            }
+        }
+
+        let kind = if (is_delimiter(c) || is_bracket(c)) && !joins_word(text, i, c) {
+            TokenKind::Delimiter
+        } else {
+            TokenKind::Word
+        };
+        if run.map(|(k, _)| k) != Some(kind) {
+            push_run(&mut tokens, text, run, i);
+            run = Some((kind, i));
+        }
+    }
+    push_run(&mut tokens, text, run, text.len());
+
+    tokens
+}
+
+fn push_run<'a>(
+    tokens: &mut Vec<Token<'a>>,
+    text: &'a str,
+    run: Option<(TokenKind, usize)>,
+    end: usize,
+) {
+    if let Some((kind, start)) = run.filter(|&(_, start)| start < end) {
+        tokens.push(Token {
+            kind,
+            text: &text[start..end],
+            bracket: None,
+        });
+    }
+}
+
+/// Whether the `-` or `.` at byte `i` belongs to the word around it
+fn joins_word(text: &str, i: usize, c: char) -> bool {
+    let before = &text[..i];
+    let after = match text.get(i + 1..) {
+        Some(after) => after,
+        None => return false,
+    };
+    let (prev, next) = match (before.chars().next_back(), after.chars().next()) {
+        (Some(prev), Some(next)) => (prev, next),
+        _ => return false,
+    };
+    let word_before = before
+        .rsplit(|ch: char| is_delimiter(ch) || is_bracket(ch))
+        .next()
+        .unwrap_or("");
+
+    match c {
+        // "Title-05" still splits, so the dash strategy sees the number
+        '-' => {
+            let segment = after
+                .split(|ch: char| is_delimiter(ch) || is_bracket(ch))
+                .next()
+                .unwrap_or("");
+            prev.is_alphanumeric()
+                && next.is_alphanumeric()
+                && !(prev.is_alphabetic() && is_episode_number(segment))
+        }
+        // "12.5" but not "01.1080p"
+        '.' => {
+            let decimal = (1..=3).contains(&word_before.len())
+                && word_before.bytes().all(|b| b.is_ascii_digit())
+                && next.is_ascii_digit()
+                && !after.chars().nth(1).is_some_and(char::is_alphanumeric);
+            let codec = (word_before.eq_ignore_ascii_case("h")
+                || word_before.eq_ignore_ascii_case("x"))
+                && (after.starts_with("264") || after.starts_with("265"));
+            decimal || codec
+        }
+        _ => false,
+    }
+}
+
+/// Join words and delimiters into a title: dots and underscores read as
+/// spaces, brackets are dropped, and dangling dashes are trimmed
+pub fn join_words(tokens: &[Token]) -> String {
+    let mut title = String::new();
+    for token in tokens {
+        match token.kind {
+            TokenKind::Word => title.push_str(token.text),
+            TokenKind::Enclosed => title.push(' '),
+            TokenKind::Delimiter => {
+                let kept: String = token
+                    .text
+                    .chars()
+                    .filter(|&c| !c.is_whitespace() && c != '_' && c != '.')
+                    .collect();
+                if kept.is_empty() {
+                    title.push(' ');
+                } else if kept.len() == token.text.len() {
+                    // "Kaguya-sama"
+                    title.push_str(&kept);
+                } else {
+                    title.push(' ');
+                    title.push_str(&kept);
+                    title.push(' ');
+                }
+            }
+        }
+    }
+
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    title
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '+' | '&' | '~' | '|'))
+        .to_string()
+}
+
+/// The word after the word at `i`, when only a delimiter separates them
+pub fn next_word<'a>(tokens: &[Token<'a>], i: usize) -> Option<(usize, &'a str)> {
+    tokens
+        .get(i + 1)
+        .filter(|t| t.kind == TokenKind::Delimiter)?;
+    tokens
+        .get(i + 2)
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| (i + 2, t.text))
+}
+
+/// Split one of `extensions` (lowercase, with dot) off a name:
+/// ("Frieren - 05", Some("mkv"))
+pub fn split_extension<'a>(name: &'a str, extensions: &[&str]) -> (&'a str, Option<String>) {
+    for ext in extensions {
+        let at = match name.len().checked_sub(ext.len()) {
+            Some(at) => at,
+            None => continue,
+        };
+        if name
+            .get(at..)
+            .is_some_and(|tail| tail.eq_ignore_ascii_case(ext))
+        {
+            return (&name[..at], Some(ext[1..].to_string()));
+        }
+    }
+    (name, None)
+}
+
+/// `05` or `05v2`
+fn is_episode_number(s: &str) -> bool {
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    let version = &s[digits..];
+    let version_ok = version.is_empty()
+        || ((2..=3).contains(&version.len())
+            && version.starts_with(['v', 'V'])
+            && version[1..].bytes().all(|b| b.is_ascii_digit()));
+    (1..=3).contains(&digits) && version_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        tokenize(text)
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Word)
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("[SubsPlease] Kaguya-sama - 05v2 (1080p)");
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Enclosed,
+                TokenKind::Delimiter,
+                TokenKind::Word,
+                TokenKind::Delimiter,
+                TokenKind::Word,
+                TokenKind::Delimiter,
+                TokenKind::Enclosed,
+            ]
+        );
+        assert_eq!(tokens[0].text, "SubsPlease");
+        assert_eq!(tokens[3].text, " - ");
+        assert_eq!(tokens[6].bracket, Some('('));
+
+        assert_eq!(
+            words("Spy.x.Family.S02E03.1080p.WEB-DL.DDP2.0.H.264-VARYG"),
+            [
+                "Spy",
+                "x",
+                "Family",
+                "S02E03",
+                "1080p",
+                "WEB-DL",
+                "DDP2",
+                "0",
+                "H.264-VARYG"
+            ]
+        );
+        assert_eq!(words("Oshi.no.Ko.12.5"), ["Oshi", "no", "Ko", "12.5"]);
+        assert_eq!(words("Frieren.01.1080p"), ["Frieren", "01", "1080p"]);
+        assert_eq!(
+            words("Vinland_Saga-05 01-24"),
+            ["Vinland", "Saga", "05", "01-24"]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_brackets_are_delimiters() {
+        assert_eq!(words("Frieren [05"), ["Frieren", "05"]);
+        assert_eq!(words("Frieren) 05"), ["Frieren", "05"]);
+        assert!(tokenize("").is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+//! Player Web Interface Module
+//!
+//! PURPOSE: Poll the built-in HTTP interfaces of VLC and MPC-HC/MPC-BE for
+//! the current file, position, duration and play state
+//!
+//! - VLC: `/requests/status.json` (enable "Web" under Main interfaces and set
+//!   a Lua HTTP password; VLC uses an empty username)
+//! - MPC-HC / MPC-BE: `/variables.html` (Options → Player → Web Interface)
+//!
+//! Both report how far into the file the user actually is, so progress can be
+//! based on real watch time instead of a window title showing up.
+//!
+//! Both are off until the user turns them on: polling a port nobody serves
+//! every few seconds is wasted work, and 8080 is often some other local
+//! server. Settings are kept in `web_players.json`.
+
+use crate::media_player::{MediaPlayer, PlaybackInfo, PlaybackState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Requests go to localhost, so anything slower than this means nobody is listening
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Connection settings for a player's web interface
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebInterfaceConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// VLC requires a password; MPC only needs it behind an auth proxy
+    pub password: Option<String>,
+}
+
+impl WebInterfaceConfig {
+    fn base_url(&self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
+}
+
+/// Settings for both players, as stored in `web_players.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct WebPlayerConfigs {
+    vlc: WebInterfaceConfig,
+    mpc: WebInterfaceConfig,
+}
+
+impl Default for WebPlayerConfigs {
+    fn default() -> Self {
+        WebPlayerConfigs {
+            vlc: WebInterfaceConfig {
+                enabled: false,
+                host: "127.0.0.1".to_string(),
+                port: 8080,
+                password: None,
+            },
+            mpc: WebInterfaceConfig {
+                enabled: false,
+                host: "127.0.0.1".to_string(),
+                port: 13579,
+                password: None,
+            },
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIGS: Mutex<WebPlayerConfigs> = Mutex::new(load_config_file());
+}
+
+fn config_path() -> PathBuf {
+    crate::app_data_dir().join("web_players.json")
+}
+
+fn load_config_file() -> WebPlayerConfigs {
+    let json = match std::fs::read_to_string(config_path()) {
+        Ok(json) => json,
+        Err(_) => return WebPlayerConfigs::default(),
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("[WebPlayers] Ignoring unreadable settings file: {}", e);
+        WebPlayerConfigs::default()
+    })
+}
+
+fn save_config_file(configs: &WebPlayerConfigs) -> Result<(), String> {
+    let path = config_path();
+    let json = serde_json::to_string_pretty(configs)
+        .map_err(|e| format!("Failed to encode web player settings: {}", e))?;
+    // Holds the VLC web interface password
+    crate::anilist_auth::write_private(&path, json.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn config_slot<'a>(
+    configs: &'a mut WebPlayerConfigs,
+    player: &str,
+) -> Result<&'a mut WebInterfaceConfig, String> {
+    match player.to_lowercase().as_str() {
+        "vlc" => Ok(&mut configs.vlc),
+        "mpc" | "mpc-hc" | "mpc-be" => Ok(&mut configs.mpc),
+        other => Err(format!("Unknown web interface player: {}", other)),
+    }
+}
+
+/// Get the web interface settings for "vlc" or "mpc"
+pub fn get_config(player: &str) -> Result<WebInterfaceConfig, String> {
+    let mut configs = CONFIGS.lock().map_err(|_| "Config lock error")?;
+    Ok(config_slot(&mut configs, player)?.clone())
+}
+
+/// Replace and save the web interface settings for "vlc" or "mpc"
+pub fn set_config(player: &str, config: WebInterfaceConfig) -> Result<(), String> {
+    let mut configs = CONFIGS.lock().map_err(|_| "Config lock error")?;
+    let mut updated = configs.clone();
+    *config_slot(&mut updated, player)? = config;
+    save_config_file(&updated)?;
+    *configs = updated;
+    Ok(())
+}
+
+/// Poll all enabled web interfaces and return the first with loaded media
+///
+/// A playing player is preferred over a paused one.
+pub async fn get_now_playing() -> Option<PlaybackInfo> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .ok()?;
+
+    let mut paused = None;
+    for result in [poll_vlc(&client).await, poll_mpc(&client).await] {
+        match result {
+            Some(info) if info.state == PlaybackState::Playing => return Some(info),
+            Some(info) if info.state == PlaybackState::Paused && paused.is_none() => {
+                paused = Some(info)
+            }
+            _ => {}
+        }
+    }
+    paused
+}
+
+async fn poll_vlc(client: &reqwest::Client) -> Option<PlaybackInfo> {
+    let config = get_config("vlc").ok().filter(|c| c.enabled)?;
+
+    let response = client
+        .get(format!("{}/requests/status.json", config.base_url()))
+        .basic_auth("", config.password.as_deref())
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        println!("[WebPlayers] VLC status request failed: {}", response.status());
+        return None;
+    }
+
+    let status: serde_json::Value = response.json().await.ok()?;
+    parse_vlc_status(&status)
+}
+
+async fn poll_mpc(client: &reqwest::Client) -> Option<PlaybackInfo> {
+    let config = get_config("mpc").ok().filter(|c| c.enabled)?;
+
+    let mut request = client.get(format!("{}/variables.html", config.base_url()));
+    if let Some(ref password) = config.password {
+        request = request.basic_auth("", Some(password));
+    }
+
+    let response = request.send().await.ok()?;
+    if !response.status().is_success() {
+        println!("[WebPlayers] MPC variables request failed: {}", response.status());
+        return None;
+    }
+
+    let html = response.text().await.ok()?;
+    parse_mpc_variables(&html)
+}
+
+/// Parse VLC's `/requests/status.json`
+pub fn parse_vlc_status(status: &serde_json::Value) -> Option<PlaybackInfo> {
+    let state = match status.get("state").and_then(|s| s.as_str())? {
+        "playing" => PlaybackState::Playing,
+        "paused" => PlaybackState::Paused,
+        _ => return None,
+    };
+
+    let meta = status
+        .get("information")
+        .and_then(|i| i.get("category"))
+        .and_then(|c| c.get("meta"));
+    let filename = meta
+        .and_then(|m| m.get("filename"))
+        .and_then(|f| f.as_str())
+        .map(String::from);
+    let title = meta
+        .and_then(|m| m.get("title"))
+        .and_then(|t| t.as_str())
+        .map(String::from);
+
+    if filename.is_none() && title.is_none() {
+        return None;
+    }
+
+    let length = status
+        .get("length")
+        .and_then(|l| l.as_f64())
+        .filter(|&l| l > 0.0);
+    // "position" is a 0-1 fraction with sub-second precision; "time" is whole seconds
+    let position = match (status.get("position").and_then(|p| p.as_f64()), length) {
+        (Some(fraction), Some(len)) => Some(fraction * len),
+        _ => status.get("time").and_then(|t| t.as_f64()),
+    };
+
+    Some(PlaybackInfo {
+        source: "vlc-http".to_string(),
+        player: MediaPlayer::VLC,
+        url: filename,
+        title,
+        position_secs: position,
+        duration_secs: length,
+        state,
+    })
+}
+
+/// Parse MPC-HC/MPC-BE's `/variables.html`
+///
+/// The page is a list of `<p id="name">value</p>` entries; times are in milliseconds.
+pub fn parse_mpc_variables(html: &str) -> Option<PlaybackInfo> {
+    let re = Regex::new(r#"<p id="([a-z]+)">([^<]*)</p>"#).ok()?;
+    let vars: std::collections::HashMap<&str, String> = re
+        .captures_iter(html)
+        .filter_map(|caps| {
+            Some((
+                caps.get(1)?.as_str(),
+                decode_html_entities(caps.get(2)?.as_str()),
+            ))
+        })
+        .collect();
+
+    // MPC states: -1 = nothing loaded, 0 = stopped, 1 = paused, 2 = playing
+    let state = match vars.get("state").map(|s| s.trim()) {
+        Some("2") => PlaybackState::Playing,
+        Some("1") => PlaybackState::Paused,
+        _ => return None,
+    };
+
+    let file = vars
+        .get("filepath")
+        .or_else(|| vars.get("file"))
+        .filter(|f| !f.is_empty())
+        .cloned()?;
+
+    let ms_to_secs = |key: &str| -> Option<f64> {
+        vars.get(key)
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .map(|ms| ms / 1000.0)
+    };
+
+    Some(PlaybackInfo {
+        source: "mpc-http".to_string(),
+        player: MediaPlayer::MPC,
+        url: Some(file),
+        title: vars.get("file").cloned(),
+        position_secs: ms_to_secs("position"),
+        duration_secs: ms_to_secs("duration").filter(|&d| d > 0.0),
+        state,
+    })
+}
+
+fn decode_html_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#039;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_vlc_status() {
+        let status = json!({
+            "state": "playing",
+            "time": 711,
+            "length": 1420,
+            "position": 0.5,
+            "information": {
+                "category": {
+                    "meta": {
+                        "filename": "[SubsPlease] Frieren - 05 (1080p) [ABCD1234].mkv"
+                    }
+                }
+            }
+        });
+
+        let info = parse_vlc_status(&status).unwrap();
+        assert_eq!(info.player, MediaPlayer::VLC);
+        assert_eq!(info.state, PlaybackState::Playing);
+        assert_eq!(info.position_secs, Some(710.0));
+        assert_eq!(info.duration_secs, Some(1420.0));
+        assert_eq!(
+            info.parse_target(),
+            Some("[SubsPlease] Frieren - 05 (1080p) [ABCD1234].mkv".to_string())
+        );
+
+        assert!(parse_vlc_status(&json!({"state": "stopped"})).is_none());
+    }
+
+    #[test]
+    fn test_parse_mpc_variables() {
+        let html = r#"<html><body>
+            <p id="file">Spy x Family - 12 [1080p].mkv</p>
+            <p id="filepath">D:\Anime\Spy x Family\Spy x Family - 12 [1080p].mkv</p>
+            <p id="state">1</p>
+            <p id="position">360000</p>
+            <p id="duration">1440000</p>
+        </body></html>"#;
+
+        let info = parse_mpc_variables(html).unwrap();
+        assert_eq!(info.player, MediaPlayer::MPC);
+        assert_eq!(info.state, PlaybackState::Paused);
+        assert_eq!(info.progress_ratio(), Some(0.25));
+        assert_eq!(
+            info.parse_target(),
+            Some("Spy x Family - 12 [1080p].mkv".to_string())
+        );
+
+        assert!(parse_mpc_variables(r#"<p id="state">-1</p>"#).is_none());
+    }
+
+    #[test]
+    fn test_config_defaults_and_lookup() {
+        let mut configs = WebPlayerConfigs::default();
+        assert!(!configs.vlc.enabled && !configs.mpc.enabled);
+
+        config_slot(&mut configs, "MPC-BE").unwrap().port = 13580;
+        assert_eq!(configs.mpc.port, 13580);
+        assert!(config_slot(&mut configs, "potplayer").is_err());
+    }
+}
@@ -1,10 +1,15 @@
 #![cfg(windows)]
 
-use winapi::shared::minwindef::{BOOL, LPARAM};
+use crate::media_player::WindowInfo;
+use winapi::shared::minwindef::{BOOL, DWORD, LPARAM};
 use winapi::shared::windef::HWND;
-use winapi::um::winnt::LPWSTR;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winbase::QueryFullProcessImageNameW;
+use winapi::um::winnt::{LPWSTR, PROCESS_QUERY_LIMITED_INFORMATION};
 use winapi::um::winuser::{
-    EnumWindows, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, IsWindowVisible,
+    EnumWindows, GetClassNameW, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW,
+    GetWindowThreadProcessId, IsWindowVisible,
 };
 
 unsafe fn get_foreground_window() -> Option<HWND> {
@@ -37,6 +42,54 @@ pub unsafe fn get_window_title(hwnd: HWND) -> Option<String> {
     String::from_utf16(&buffer[..written as usize]).ok()
 }
 
+/// Get the Win32 class name of a window (e.g. "MediaPlayerClassicW")
+unsafe fn get_window_class(hwnd: HWND) -> Option<String> {
+    let mut buffer: [u16; 256] = [0; 256];
+    let written = GetClassNameW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+
+    if written <= 0 {
+        return None;
+    }
+
+    String::from_utf16(&buffer[..written as usize]).ok()
+}
+
+/// Get the executable name (e.g. "vlc.exe") of the process owning a window
+unsafe fn get_process_name(hwnd: HWND) -> Option<String> {
+    let mut pid: DWORD = 0;
+    GetWindowThreadProcessId(hwnd, &mut pid);
+    if pid == 0 {
+        return None;
+    }
+
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+    if handle.is_null() {
+        return None;
+    }
+
+    let mut buffer: Vec<u16> = vec![0; 1024];
+    let mut size = buffer.len() as DWORD;
+    let ok = QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut size);
+    CloseHandle(handle);
+
+    if ok == 0 {
+        return None;
+    }
+
+    let path = String::from_utf16(&buffer[..size as usize]).ok()?;
+    path.rsplit('\\').next().map(String::from)
+}
+
+unsafe fn get_window_info(hwnd: HWND) -> Option<WindowInfo> {
+    let title = get_window_title(hwnd)?;
+
+    Some(WindowInfo {
+        title,
+        process_name: get_process_name(hwnd),
+        class_name: get_window_class(hwnd),
+    })
+}
+
 pub fn get_active_window_title() -> Option<String> {
     unsafe {
         let hwnd = get_foreground_window()?;
@@ -44,20 +97,28 @@ pub fn get_active_window_title() -> Option<String> {
     }
 }
 
+/// Title, process and class of the foreground window
+pub fn get_active_window_info() -> Option<WindowInfo> {
+    unsafe {
+        let hwnd = get_foreground_window()?;
+        get_window_info(hwnd)
+    }
+}
+
 /// Callback for EnumWindows to collect all visible windows
 unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
-    let titles = &mut *(lparam as *mut Vec<String>);
-    if let Some(title) = get_window_title(hwnd) {
-        titles.push(title);
+    let windows = &mut *(lparam as *mut Vec<WindowInfo>);
+    if let Some(info) = get_window_info(hwnd) {
+        windows.push(info);
     }
     1 // Continue enumeration
 }
 
-/// Returns titles of all visible windows
-pub fn get_all_visible_window_titles() -> Vec<String> {
-    let mut titles: Vec<String> = Vec::new();
+/// Returns title, process and class of all visible windows
+pub fn get_all_visible_windows() -> Vec<WindowInfo> {
+    let mut windows: Vec<WindowInfo> = Vec::new();
     unsafe {
-        EnumWindows(Some(enum_windows_callback), &mut titles as *mut _ as LPARAM);
+        EnumWindows(Some(enum_windows_callback), &mut windows as *mut _ as LPARAM);
     }
-    titles
+    windows
 }
@@ -8,7 +8,7 @@ import { trackAnimeSession } from '../../services/StatsService';
 import './FloatingNowPlaying.css';
 
 interface DetectionResult {
-    status: 'detected' | 'not_media_player' | 'no_window';
+    status: 'detected' | 'not_media_player' | 'no_window' | 'paused';
     player?: string;
     window_title?: string;
     parsed?: {
@@ -7,7 +7,7 @@ import * as malClient from '../../api/malClient';
 import { trackAnimeSession } from '../../services/StatsService';
 
 interface DetectionResult {
-    status: 'detected' | 'not_media_player' | 'no_window';
+    status: 'detected' | 'not_media_player' | 'no_window' | 'paused';
     player?: string;
     window_title?: string;
     parsed?: {
@@ -29,14 +29,15 @@ interface DetectionResult {
     } | null;
 }
 
-interface ProgressiveSearchResult {
-    title: {
-        english: string | null;
-        romaji: string | null;
+interface TitleMatch {
+    anime: {
+        id: number;
+        title: {
+            english: string | null;
+            romaji: string | null;
+        };
     };
-    matched_query: string;
-    words_used: number;
-    total_words: number;
+    score: number;
 }
 
 interface NowPlayingProps {
@@ -48,7 +49,7 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
     const [detection, setDetection] = useState<DetectionResult | null>(null);
     const [isLoading, setIsLoading] = useState(true);
     const [error, setError] = useState<string | null>(null);
-    const [progressiveResult, setProgressiveResult] = useState<ProgressiveSearchResult | null>(null);
+    const [matchResult, setMatchResult] = useState<TitleMatch | null>(null);
     const [isSearching, setIsSearching] = useState(false);
     const [syncStatus, setSyncStatus] = useState<'idle' | 'waiting' | 'saving' | 'syncing' | 'synced' | 'error'>('idle');
     const [watchProgress, setWatchProgress] = useState<number>(0); // 0-100%
@@ -69,18 +70,17 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
     const DEBUG_MODE = false; // Set to true for testing with 30 second threshold
     const SYNC_THRESHOLD_MS = DEBUG_MODE ? 30 * 1000 : 10 * 60 * 1000;
 
-    // Test progressive search with a sample title
-    const testProgressiveSearch = async () => {
+    // Test title matching with a sample title
+    const testTitleMatch = async () => {
         setIsSearching(true);
         try {
             const testTitle = detection?.parsed?.title || 'Frieren Beyond Journey End';
-            console.log('[Test] Starting progressive search for:', testTitle);
-            const result = await invoke<string>('progressive_search_command', { title: testTitle });
-            const parsed: ProgressiveSearchResult | null = JSON.parse(result);
-            console.log('[Test] Progressive search result:', parsed);
-            setProgressiveResult(parsed);
+            console.log('[Test] Matching title:', testTitle);
+            const result = await invoke<TitleMatch | null>('match_title_command', { title: testTitle });
+            console.log('[Test] Title match result:', result);
+            setMatchResult(result);
         } catch (err) {
-            console.error('[Test] Progressive search error:', err);
+            console.error('[Test] Title match error:', err);
         } finally {
             setIsSearching(false);
         }
@@ -532,7 +532,7 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
                     </div>
                 )}
 
-                {/* Test Progressive Search Section */}
+                {/* Test Title Match Section */}
                 <div style={{
                     marginTop: '1rem',
                     padding: '1rem',
@@ -541,9 +541,9 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
                     border: '1px solid rgba(180, 162, 246, 0.2)',
                 }}>
                     <div style={{ display: 'flex', alignItems: 'center', gap: '0.75rem', marginBottom: '0.75rem' }}>
-                        <span style={{ fontSize: '0.9rem', color: '#B4A2F6' }}>🔬 Test Progressive Search</span>
+                        <span style={{ fontSize: '0.9rem', color: '#B4A2F6' }}>🔬 Test Title Match</span>
                         <button
-                            onClick={testProgressiveSearch}
+                            onClick={testTitleMatch}
                             disabled={isSearching}
                             style={{
                                 padding: '0.4rem 0.8rem',
@@ -560,7 +560,7 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
                             {isSearching ? 'Searching...' : 'Test Search'}
                         </button>
                     </div>
-                    {progressiveResult && (
+                    {matchResult && (
                         <div style={{
                             fontSize: '0.85rem',
                             color: '#9CA3AF',
@@ -570,10 +570,10 @@ export function NowPlaying({ onAnimeDetected }: NowPlayingProps) {
                         }}>
                             <div style={{ marginBottom: '0.25rem' }}>
                                 <strong style={{ color: '#86EFAC' }}>Found:</strong>{' '}
-                                {progressiveResult.title.english || progressiveResult.title.romaji}
+                                {matchResult.anime.title.english || matchResult.anime.title.romaji}
                             </div>
                             <div style={{ fontSize: '0.75rem', opacity: 0.7 }}>
-                                Matched with "{progressiveResult.matched_query}" ({progressiveResult.words_used}/{progressiveResult.total_words} words)
+                                Match score {Math.round(matchResult.score * 100)}%
                             </div>
                         </div>
                     )}
@@ -60,7 +60,7 @@ function parseEpisodeFromString(str: string): number | null {
 }
 
 interface DetectedAnime {
-    status: 'detected' | 'not_media_player' | 'no_window';
+    status: 'detected' | 'not_media_player' | 'no_window' | 'paused';
     player?: string;
     window_title?: string;
     parsed?: {